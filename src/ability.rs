@@ -0,0 +1,416 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::champion::stats::ChampStats;
+use crate::constants::{MAX_LEVEL, TICK_SECOND};
+use crate::damage::Damage;
+use crate::effects::{CrowdControl, DamageFormula, DamageType};
+use crate::shield::ShieldEffect;
+
+const ULTIMATE_LEVELS: [i32; 3] = [6, 11, 16];
+const MAX_BASIC_RANK: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbilitySlot {
+    Passive,
+    Q,
+    W,
+    E,
+    R,
+}
+
+/// Seconds taken off an ability's remaining cooldown when it hits, or when it kills its target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CooldownRefund {
+    pub(crate) on_hit: f32,
+    pub(crate) on_kill: f32,
+}
+
+/// An ability as it is defined in champion data. Per-rank values are listed from rank 1 upwards;
+/// an empty list means the ability has no damage, cooldown or cost.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Ability {
+    pub(crate) name: String,
+    pub(crate) slot: AbilitySlot,
+    pub(crate) damage_type: DamageType,
+    #[serde(default)]
+    pub(crate) base_damage: Vec<f32>,
+    // Added to the base damage for the rank; the caps apply to the total
+    #[serde(default)]
+    pub(crate) ratios: DamageFormula,
+    #[serde(default)]
+    pub(crate) cooldown: Vec<f32>, // In seconds
+    #[serde(default)]
+    pub(crate) cost: Vec<f32>,
+    #[serde(default)]
+    pub(crate) cast_time: f32, // In seconds
+    pub(crate) range: i32,
+    // Chance for an average player to land the ability; abilities without one always hit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) hit_chance: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) crowd_control: Option<CrowdControl>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) cooldown_refund: Option<CooldownRefund>,
+    // Hits an area rather than a single target, which makes omnivamp less effective
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) area: bool,
+    // Healing for the caster when the ability hits; target ratios use the champion that is hit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) healing: Option<DamageFormula>,
+    // Shield for the caster whenever the ability is cast, even if it misses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) shield: Option<ShieldEffect>,
+    // Lets the caster attack again as soon as the cast is done
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) resets_attack: bool,
+}
+
+/// The order basic abilities are maxed in, e.g. `Q>E>W`. The first three levels learn one rank of
+/// each basic ability in that order, the ultimate is ranked up at levels 6, 11 and 16, and every
+/// other level goes to the first ability in the order that can still be ranked up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SkillOrder {
+    pub(crate) order: Vec<AbilitySlot>,
+}
+
+/// An ability's state during a fight, kept on the champion that owns it.
+#[derive(Debug, Clone)]
+pub struct AbilityState {
+    pub(crate) ability: Ability,
+    pub(crate) rank: usize, // 0 until the ability is learned
+    pub(crate) cooldown_left: Duration,
+    pub(crate) damage_dealt: Damage, // After mitigation
+    pub(crate) casts: i32,
+    pub(crate) hits: i32,
+}
+
+impl fmt::Display for AbilitySlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AbilitySlot::Passive => "Passive",
+            AbilitySlot::Q => "Q",
+            AbilitySlot::W => "W",
+            AbilitySlot::E => "E",
+            AbilitySlot::R => "R",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl AbilitySlot {
+    /// Parse a slot letter (`q`, `w`, `e` or `r`), or `passive`. Case is ignored.
+    pub fn from_name(name: &str) -> Option<AbilitySlot> {
+        match name.trim().to_lowercase().as_str() {
+            "passive" | "p" => Some(AbilitySlot::Passive),
+            "q" => Some(AbilitySlot::Q),
+            "w" => Some(AbilitySlot::W),
+            "e" => Some(AbilitySlot::E),
+            "r" => Some(AbilitySlot::R),
+            _ => None,
+        }
+    }
+}
+
+impl SkillOrder {
+    /// Parse basic ability slots separated by `>`, e.g. `Q>E>W`. Each of Q, W and E must appear once;
+    /// R may be left out as it is always ranked up at the same levels.
+    pub fn parse(text: &str) -> Option<SkillOrder> {
+        let order = text.split('>')
+            .map(AbilitySlot::from_name)
+            .filter(|slot| *slot != Some(AbilitySlot::R))
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut sorted = order.clone();
+        sorted.sort();
+
+        (sorted == [AbilitySlot::Q, AbilitySlot::W, AbilitySlot::E]).then_some(SkillOrder { order })
+    }
+
+    /// The rank of each ability once a champion has reached `level`.
+    pub fn ranks_at(&self, level: i32) -> BTreeMap<AbilitySlot, usize> {
+        let mut ranks: BTreeMap<AbilitySlot, usize> = BTreeMap::new();
+
+        for level in 1..=level.min(MAX_LEVEL) {
+            let slot = if ULTIMATE_LEVELS.contains(&level) {
+                Some(AbilitySlot::R)
+            } else if level <= self.order.len() as i32 {
+                Some(self.order[level as usize - 1])
+            } else {
+                // A basic ability can have at most one rank for every two levels
+                let max_rank = (((level + 1) / 2) as usize).min(MAX_BASIC_RANK);
+
+                self.order.iter().copied().find(|slot| ranks.get(slot).copied().unwrap_or(0) < max_rank)
+            };
+
+            if let Some(slot) = slot {
+                *ranks.entry(slot).or_insert(0) += 1;
+            }
+        }
+
+        ranks
+    }
+}
+
+impl fmt::Display for SkillOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let slots: Vec<String> = self.order.iter().map(|slot| slot.to_string()).collect();
+
+        write!(f, "{}", slots.join(">"))
+    }
+}
+
+impl TryFrom<String> for SkillOrder {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<SkillOrder, String> {
+        SkillOrder::parse(&text).ok_or_else(|| format!("invalid skill order '{}', expected e.g. 'Q>E>W'", text))
+    }
+}
+
+impl From<SkillOrder> for String {
+    fn from(order: SkillOrder) -> String {
+        order.to_string()
+    }
+}
+
+impl Ability {
+    /// The highest rank the ability can be levelled to. Passives always have a single rank.
+    pub fn max_rank(&self) -> usize {
+        if self.slot == AbilitySlot::Passive {
+            return 1;
+        }
+
+        self.base_damage.len().max(self.cooldown.len()).max(self.cost.len())
+    }
+
+    /// Damage before mitigation when cast at `rank` by a champion with `stats` at one with `target` stats.
+    pub fn damage(&self, rank: usize, stats: &ChampStats, target: &ChampStats) -> Damage {
+        let formula = DamageFormula { flat: self.ratios.flat + value_at_rank(&self.base_damage, rank), ..self.ratios };
+
+        Damage::from_type(&self.damage_type, formula.amount(stats, target))
+    }
+
+    /// The cooldown at `rank` before ability haste.
+    pub fn cooldown(&self, rank: usize) -> Duration {
+        Duration::from_secs_f32(value_at_rank(&self.cooldown, rank))
+    }
+
+    /// The cooldown at `rank` after the ability haste in `stats`.
+    pub fn hasted_cooldown(&self, rank: usize, stats: &ChampStats) -> Duration {
+        self.cooldown(rank).mul_f32(stats.cooldown_multiplier(self.slot == AbilitySlot::R))
+    }
+
+    pub fn cost(&self, rank: usize) -> f32 {
+        value_at_rank(&self.cost, rank)
+    }
+}
+
+/// The value for `rank` from a per-rank list. Ranks past the end of the list use the last value.
+fn value_at_rank(values: &[f32], rank: usize) -> f32 {
+    match rank {
+        0 => 0.0,
+        rank => values.get(rank - 1).or(values.last()).copied().unwrap_or(0.0),
+    }
+}
+
+impl AbilityState {
+    pub fn new(ability: Ability) -> AbilityState {
+        // Passives are always available
+        let rank = if ability.slot == AbilitySlot::Passive { 1 } else { 0 };
+
+        AbilityState {
+            ability,
+            rank,
+            cooldown_left: Duration::ZERO,
+            damage_dealt: Damage::new(0.0, 0.0, 0.0),
+            casts: 0,
+            hits: 0,
+        }
+    }
+
+    pub fn set_rank(&mut self, rank: usize) {
+        if self.ability.slot != AbilitySlot::Passive {
+            self.rank = rank.min(self.ability.max_rank());
+        }
+    }
+
+    /// Whether the ability is learned, off cooldown and can be cast at all (passives cannot).
+    pub fn is_ready(&self) -> bool {
+        self.ability.slot != AbilitySlot::Passive && self.rank > 0 && self.cooldown_left.is_zero()
+    }
+
+    pub fn start_cooldown(&mut self, stats: &ChampStats) {
+        self.cooldown_left = self.ability.hasted_cooldown(self.rank, stats);
+    }
+
+    /// Take `seconds` off the remaining cooldown.
+    pub fn refund_cooldown(&mut self, seconds: f32) {
+        self.cooldown_left = self.cooldown_left.saturating_sub(Duration::from_secs_f32(seconds));
+    }
+
+    /// Advance the cooldown by one tick.
+    pub fn tick(&mut self) {
+        self.cooldown_left = self.cooldown_left.saturating_sub(Duration::from_secs_f32(TICK_SECOND));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::collections::BTreeMap;
+    use crate::ability::{value_at_rank, Ability, AbilitySlot, AbilityState, SkillOrder};
+    use crate::effects::{DamageFormula, DamageType};
+    use crate::utils::create_champion_by_name;
+
+    fn ability() -> Ability {
+        Ability {
+            name: String::from("Test Strike"),
+            slot: AbilitySlot::Q,
+            damage_type: DamageType::Physical,
+            base_damage: vec![50.0, 80.0, 110.0],
+            ratios: DamageFormula { ad: 1.0, bonus_ad: 0.5, max_health: 0.02, ..DamageFormula::default() },
+            cooldown: vec![10.0, 9.0, 8.0],
+            cost: vec![],
+            cast_time: 0.25,
+            range: 300,
+            hit_chance: None,
+            crowd_control: None,
+            cooldown_refund: None,
+            area: false,
+            healing: None,
+            shield: None,
+            resets_attack: false,
+        }
+    }
+
+    #[test]
+    fn test_value_at_rank() {
+        let values = [10.0, 20.0, 30.0];
+
+        assert_eq!(value_at_rank(&values, 0), 0.0);
+        assert_eq!(value_at_rank(&values, 2), 20.0);
+        assert_eq!(value_at_rank(&values, 5), 30.0);
+        assert_eq!(value_at_rank(&[], 1), 0.0);
+    }
+
+    #[test]
+    fn test_ability_damage() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let target = create_champion_by_name("dummy").unwrap();
+        champion.champ_stats.bonus_ad = 20.0;
+        champion.champ_stats.max_health = 1000.0;
+
+        let damage = ability().damage(2, &champion.champ_stats, &target.champ_stats);
+
+        assert_eq!(damage.physical_component, 80.0 + 60.0 + 10.0 + 20.0);
+        assert_eq!(damage.magical_component, 0.0);
+        assert_eq!(ability().damage(0, &champion.champ_stats, &target.champ_stats).physical_component, 90.0);
+    }
+
+    #[test]
+    fn test_ability_damage_target_health() {
+        let champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut target = create_champion_by_name("dummy").unwrap();
+        target.champ_stats.health = 6000.0;
+
+        let mut ability = ability();
+        ability.base_damage = vec![];
+        ability.ratios = DamageFormula { target_missing_health: 0.1, target_current_health: 0.01, ..DamageFormula::default() };
+
+        assert_eq!(ability.damage(1, &champion.champ_stats, &target.champ_stats).physical_component, 400.0 + 60.0);
+
+        ability.ratios = DamageFormula { target_max_health: 0.03, ..DamageFormula::default() };
+        assert_eq!(ability.damage(1, &champion.champ_stats, &target.champ_stats).physical_component, 300.0);
+
+        // The caps apply to the base damage and ratios together
+        ability.base_damage = vec![100.0];
+        ability.ratios.max = Some(350.0);
+        assert_eq!(ability.damage(1, &champion.champ_stats, &target.champ_stats).physical_component, 350.0);
+    }
+
+    #[test]
+    fn test_ability_state() {
+        let champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut state = AbilityState::new(ability());
+        assert!(!state.is_ready());
+
+        state.set_rank(7);
+        assert_eq!(state.rank, 3);
+        assert!(state.is_ready());
+
+        state.start_cooldown(&champion.champ_stats);
+        assert_eq!(state.cooldown_left, Duration::from_secs(8));
+        assert!(!state.is_ready());
+
+        for _ in 0..240 {
+            state.tick();
+        }
+
+        assert!(state.is_ready());
+    }
+
+    #[test]
+    fn test_passive_is_never_cast() {
+        let mut passive = ability();
+        passive.slot = AbilitySlot::Passive;
+
+        let mut state = AbilityState::new(passive);
+        state.set_rank(0);
+
+        assert_eq!(state.rank, 1);
+        assert!(!state.is_ready());
+    }
+
+    #[test]
+    fn test_hasted_cooldown_and_refund() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        champion.champ_stats.ability_haste = 25.0;
+        champion.champ_stats.ultimate_haste = 75.0;
+
+        let mut state = AbilityState::new(ability());
+        state.set_rank(3);
+        state.start_cooldown(&champion.champ_stats);
+        assert_eq!(state.cooldown_left, Duration::from_secs_f32(6.4));
+
+        state.refund_cooldown(2.0);
+        assert_eq!(state.cooldown_left, Duration::from_secs_f32(6.4).saturating_sub(Duration::from_secs(2)));
+
+        state.refund_cooldown(10.0);
+        assert!(state.is_ready());
+
+        let mut ultimate = ability();
+        ultimate.slot = AbilitySlot::R;
+        assert_eq!(ultimate.hasted_cooldown(1, &champion.champ_stats), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_parse_skill_order() {
+        let order = SkillOrder::parse("q>E>w").unwrap();
+
+        assert_eq!(order.order, vec![AbilitySlot::Q, AbilitySlot::E, AbilitySlot::W]);
+        assert_eq!(order.to_string(), "Q>E>W");
+        assert_eq!(SkillOrder::parse("R>Q>E>W"), Some(order));
+        assert!(SkillOrder::parse("Q>E").is_none());
+        assert!(SkillOrder::parse("Q>Q>W").is_none());
+        assert!(SkillOrder::parse("Q>E>X").is_none());
+    }
+
+    #[test]
+    fn test_skill_order_ranks() {
+        let order = SkillOrder::parse("Q>E>W").unwrap();
+        let ranks = |level| order.ranks_at(level).into_iter().collect::<Vec<_>>();
+
+        assert!(order.ranks_at(0).is_empty());
+        assert_eq!(ranks(3), vec![(AbilitySlot::Q, 1), (AbilitySlot::W, 1), (AbilitySlot::E, 1)]);
+        assert_eq!(ranks(6), vec![(AbilitySlot::Q, 3), (AbilitySlot::W, 1), (AbilitySlot::E, 1), (AbilitySlot::R, 1)]);
+        assert_eq!(ranks(9), vec![(AbilitySlot::Q, 5), (AbilitySlot::W, 1), (AbilitySlot::E, 2), (AbilitySlot::R, 1)]);
+        assert_eq!(order.ranks_at(18), BTreeMap::from([(AbilitySlot::Q, 5), (AbilitySlot::W, 5), (AbilitySlot::E, 5), (AbilitySlot::R, 3)]));
+    }
+}
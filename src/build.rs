@@ -1,10 +1,12 @@
 use crate::champion::Champion;
 use crate::item::Item;
+use crate::keystone::Keystone;
+use crate::rune::RunePage;
 
 pub struct Build {
     pub(crate) champion: Champion,
     pub(crate) items: Vec<Item>,
-    // runes: Vec<Rune>,
+    pub(crate) rune_page: Option<RunePage>,
 }
 
 impl Build {
@@ -12,6 +14,127 @@ impl Build {
         Build {
             champion: champion.clone(),
             items,
+            rune_page: None,
         }
     }
-}
\ No newline at end of file
+
+    /// Use a rune page. The page should already be validated with `RuneRegistry::validate_page`.
+    pub fn with_rune_page(mut self, rune_page: RunePage) -> Build {
+        self.rune_page = Some(rune_page);
+        self
+    }
+
+    /// The champion with the stats and effects from every item and the rune page applied at its current
+    /// level.
+    pub fn built_champion(&self) -> Champion {
+        let mut champion = self.champion.clone();
+
+        for item in &self.items {
+            champion.champ_stats.add_item_stats(item);
+            champion.add_item_effects(item);
+        }
+
+        // After items, as adaptive force depends on the bonus stats from items
+        if let Some(rune_page) = &self.rune_page {
+            rune_page.add_stats(&mut champion.champ_stats, champion.level);
+
+            if let Some(keystone) = Keystone::from_name(&rune_page.keystone) {
+                champion.set_keystone(keystone);
+            }
+        }
+
+        champion.set_level(champion.level);
+        champion
+    }
+
+    pub fn item_names(&self) -> Vec<&str> {
+        self.items.iter().map(|item| item.name.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::build::Build;
+    use crate::data::{data_dir, read_json_file};
+    use crate::data::patch::GameData;
+    use crate::utils::create_champion_by_name;
+
+    #[test]
+    fn test_built_champion_without_items() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        champion.set_level(6);
+
+        let built = Build::new(&champion, vec![]).built_champion();
+
+        assert_eq!(built.champ_stats.health, champion.champ_stats.health);
+        assert_eq!(built.champ_stats.ad, champion.champ_stats.ad);
+        assert_eq!(built.champ_stats.as_, champion.champ_stats.as_);
+        assert_eq!(built.champ_stats.armor, champion.champ_stats.armor);
+    }
+
+    #[test]
+    fn test_built_champion_with_rune_page() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let mut champion = data.create_champion("test-bruiser").unwrap();
+        champion.set_level(18);
+
+        let page = read_json_file(&data_dir().unwrap().join("rune-pages").join("aatrox-conqueror.json")).unwrap();
+        let built = Build::new(&champion, vec![data.find_item("Long Sword").unwrap()])
+            .with_rune_page(page)
+            .built_champion();
+
+        // Two adaptive force shards and the scaling health shard
+        assert_eq!(built.champ_stats.bonus_ad, 20.8);
+        assert_eq!(built.champ_stats.health, champion.champ_stats.health + 140.0);
+    }
+
+    #[test]
+    fn test_built_champion_with_items() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let champion = data.create_champion("test-bruiser").unwrap();
+        let items = vec![
+            data.find_item("Long Sword").unwrap(),
+            data.find_item("Doran's Blade").unwrap(),
+            data.find_item("Berserker's Greaves").unwrap(),
+            data.find_item("Cloth Armor").unwrap(),
+            data.find_item("Null-Magic Mantle").unwrap(),
+        ];
+
+        let build = Build::new(&champion, items);
+        let built = build.built_champion();
+        let stats = &built.champ_stats;
+
+        assert_eq!(stats.health, 765.0);
+        assert_eq!(stats.ad, 78);
+        assert_eq!(stats.bonus_ad, 18.0);
+        assert_eq!(stats.as_, 0.651 + 0.25 * 0.651);
+        assert_eq!(stats.ms, 390);
+
+        // Bonus resistances are kept apart from the base values
+        assert_eq!(stats.armor, 38.0);
+        assert_eq!(stats.bonus_armor, 15.0);
+        assert_eq!(stats.mr, 32.0);
+        assert_eq!(stats.bonus_mr, 25.0);
+
+        // The build's own champion is left as it was
+        assert_eq!(build.champion.champ_stats.ad, 60);
+        assert_eq!(build.item_names().len(), 5);
+    }
+
+    #[test]
+    fn test_built_champion_with_item_effects() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let champion = data.create_champion("test-bruiser").unwrap();
+        let items = vec![
+            data.find_item("Blade of the Ruined King").unwrap(),
+            data.find_item("Liandry's Torment").unwrap(),
+        ];
+
+        let built = Build::new(&champion, items).built_champion();
+
+        assert!(built.friendly_limited_use_on_hit_effects.contains_key("Blade of The Ruined King"));
+        assert!(built.friendly_duration_on_hit_effects.contains_key("Liandry's Torment"));
+        assert_eq!(built.champ_stats.ap, 90.0);
+        assert!(champion.friendly_limited_use_on_hit_effects.is_empty());
+    }
+}
@@ -1,63 +1,321 @@
 pub(crate) mod stats;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 use rand::Rng;
+use crate::ability::{AbilitySlot, AbilityState, SkillOrder};
 use crate::champion::stats::{calculate_crit_damage_multiplier_from_target, ChampStats};
-use crate::constants::TICK_SECOND;
+use crate::constants::{HEALTH_REGEN_INTERVAL, TICK_SECOND};
+use crate::crit::{CritModel, CritState};
 
 use crate::damage::Damage;
-use crate::effects::{DamageType, DoTEffect, LimitedUseOnHitEffect, StackingOnHitEffect};
+use crate::effects::{CrowdControl, CrowdControlType, DamageFormula, DamageType, DoTEffect, EffectTickRate, LimitedUseOnHitEffect, StackingOnHitEffect, UNLIMITED_USES};
+use crate::item::Item;
+use crate::keystone::{HitSource, Keystone, KeystoneHit, KeystoneState};
+use crate::shield::{ShieldStack, ShieldType};
 
 #[derive(Clone)]
 pub struct Champion {
     pub(crate) name: String,
     pub(crate) level: i32,
     pub(crate) champ_stats: ChampStats,
-    pub(crate) friendly_limited_use_on_hit_effects: HashMap<String, LimitedUseOnHitEffect>,
-    pub(crate) friendly_duration_on_hit_effects: HashMap<String, DoTEffect>,
-    pub(crate) friendly_stacking_on_hit_effects: HashMap<String, StackingOnHitEffect>,
-    pub(crate) enemy_dot_on_hit_effects: HashMap<String, DoTEffect>,
-    pub(crate) enemy_stacking_on_hit_effects: HashMap<String, StackingOnHitEffect>,
+    pub(crate) friendly_limited_use_on_hit_effects: BTreeMap<String, LimitedUseOnHitEffect>,
+    pub(crate) friendly_duration_on_hit_effects: BTreeMap<String, DoTEffect>,
+    pub(crate) friendly_stacking_on_hit_effects: BTreeMap<String, StackingOnHitEffect>,
+    pub(crate) enemy_dot_on_hit_effects: BTreeMap<String, DoTEffect>,
+    pub(crate) enemy_stacking_on_hit_effects: BTreeMap<String, StackingOnHitEffect>,
+    pub(crate) keystone: Option<KeystoneState>,
+    pub(crate) abilities: BTreeMap<AbilitySlot, AbilityState>,
+    pub(crate) skill_order: Option<SkillOrder>, // Sets the ability ranks whenever the level changes
+    pub(crate) crowd_control: HashMap<CrowdControlType, Duration>, // Time left of each effect on this champion
+    pub(crate) healing_done: f32, // Everything healed except for health regeneration
+    pub(crate) shields: ShieldStack,
+    pub(crate) crit_state: CritState,
 }
 
 impl Champion {
     /// Set the level of the champion. This will recalculate the base stats of the champion based on
-    /// the level supplied.
+    /// the level supplied, and the ability ranks if the champion has a skill order.
     pub fn set_level(&mut self, level: i32) {
         self.level = level;
         self.champ_stats.calculate_stats_from_level(level);
+
+        if let Some(skill_order) = &self.skill_order {
+            let ranks = skill_order.ranks_at(level);
+
+            for (slot, ability) in self.abilities.iter_mut() {
+                ability.set_rank(ranks.get(slot).copied().unwrap_or(0));
+            }
+        }
+    }
+
+    /// Follow `skill_order`, or keep the current ranks with `None`. Takes effect on the next
+    /// `set_level`.
+    pub fn set_skill_order(&mut self, skill_order: Option<SkillOrder>) {
+        self.skill_order = skill_order;
+    }
+
+    pub fn set_crit_model(&mut self, model: CritModel) {
+        self.crit_state = CritState::new(model);
+    }
+
+    pub fn set_keystone(&mut self, keystone: Keystone) {
+        self.keystone = Some(KeystoneState::new(keystone));
+    }
+
+    pub fn max_health(&self) -> f32 {
+        self.champ_stats.max_health
+    }
+
+    /// Heal up to max health and add it to `healing_done`. Returns the amount actually healed; see
+    /// `ChampStats::overheal` for the rest.
+    pub fn heal(&mut self, amount: f32) -> f32 {
+        let healed = self.restore_health(amount);
+        self.healing_done += healed;
+
+        healed
+    }
+
+    fn restore_health(&mut self, amount: f32) -> f32 {
+        let healed = amount.max(0.0) - self.champ_stats.overheal(amount.max(0.0));
+        self.champ_stats.health += healed;
+
+        healed
+    }
+
+    /// Regenerate one step of `hp5`, which happens every `HEALTH_REGEN_INTERVAL`. Dead champions do
+    /// not regenerate. Returns the amount actually healed.
+    pub fn regenerate_health(&mut self) -> f32 {
+        if self.champ_stats.health <= 0.0 {
+            return 0.0;
+        }
+
+        self.restore_health(self.champ_stats.hp5 / 5.0 * HEALTH_REGEN_INTERVAL)
+    }
+
+    /// Set the rank of an ability, capped at its highest rank. Does nothing if the champion has no
+    /// ability in that slot.
+    pub fn set_ability_rank(&mut self, slot: AbilitySlot, rank: usize) {
+        if let Some(ability) = self.abilities.get_mut(&slot) {
+            ability.set_rank(rank);
+        }
+    }
+
+    pub fn has_learned(&self, slot: AbilitySlot) -> bool {
+        self.abilities.get(&slot).is_some_and(|ability| ability.rank > 0)
+    }
+
+    /// Whether the ability in `slot` is ready and the champion can pay its cost.
+    pub fn can_cast(&self, slot: AbilitySlot) -> bool {
+        self.abilities.get(&slot).is_some_and(|ability| self.can_afford(ability))
+    }
+
+    fn can_afford(&self, ability: &AbilityState) -> bool {
+        ability.is_ready() && self.champ_stats.can_pay(ability.ability.cost(ability.rank))
+    }
+
+    /// Take the damage and crowd control from `_source` casting the ability in `slot`, which puts the
+    /// ability on cooldown. Ability damage is mitigated the same way as auto attacks. The returned damage
+    /// includes what the hit triggered on the keystone. Returns `None` without casting if the ability is
+    /// not ready.
+    pub fn take_ability_damage(&mut self, _source: &mut Champion, slot: AbilitySlot) -> Option<Damage> {
+        let effective_armor = self.champ_stats.calculate_armor_reduction(&_source.champ_stats);
+        let effective_mr = self.champ_stats.calculate_magic_resist_reduction(&_source.champ_stats);
+
+        let damage_pre_mit = _source.cast_ability(slot, &self.champ_stats)?;
+        let damage_amp = _source.keystone.as_ref().map_or(1.0, |keystone| keystone.damage_amp());
+        let damage = self.calculate_mitigated_damage(effective_armor, effective_mr, damage_pre_mit) * damage_amp;
+
+        let keystone_hit = _source.trigger_keystone_on_hit(HitSource::Ability(slot));
+        let keystone_damage = self.calculate_mitigated_damage(effective_armor, effective_mr, keystone_hit.damage) * damage_amp;
+        let total_damage = damage + keystone_damage;
+
+        self.take_damage(total_damage);
+
+        if let Some(ability) = _source.abilities.get(&slot) {
+            let healing = ability.ability.healing.map_or(0.0, |healing| healing.amount(&_source.champ_stats, &self.champ_stats));
+            let vamp = _source.champ_stats.vamp_healing(total_damage, false, ability.ability.area);
+            _source.heal(healing + vamp);
+        }
+
+        _source.record_keystone_hit(keystone_damage, keystone_hit.healing, total_damage.total());
+
+        if let Some(ability) = _source.abilities.get_mut(&slot) {
+            ability.damage_dealt += damage;
+            ability.hits += 1;
+
+            if let Some(crowd_control) = ability.ability.crowd_control {
+                self.apply_crowd_control(crowd_control);
+            }
+
+            if let Some(refund) = ability.ability.cooldown_refund {
+                ability.refund_cooldown(refund.on_hit);
+
+                if self.champ_stats.health <= 0.0 {
+                    ability.refund_cooldown(refund.on_kill);
+                }
+            }
+        }
+
+        Some(total_damage)
     }
 
-    pub fn take_auto_attack_damage(&mut self, _source: &mut Champion) -> Damage {
-        let effective_armor = self.champ_stats.calculate_armor_reduction(&mut _source.champ_stats);
-        let effective_mr = self.champ_stats.calculate_magic_resist_reduction(&mut _source.champ_stats);
+    /// Cast an ability at a target with `target` stats, pay its cost and put it on cooldown. Returns its
+    /// damage before mitigation, or `None` if the ability is not ready or the champion cannot pay for
+    /// it. Use `take_ability_damage` to cast an ability that hits.
+    pub fn cast_ability(&mut self, slot: AbilitySlot, target: &ChampStats) -> Option<Damage> {
+        if !self.can_cast(slot) {
+            return None;
+        }
+
+        let ability = self.abilities.get_mut(&slot)?;
+
+        self.champ_stats.pay(ability.ability.cost(ability.rank));
+        ability.start_cooldown(&self.champ_stats);
+        ability.casts += 1;
+
+        if let Some(shield) = &ability.ability.shield {
+            self.shields.add(shield.shield(&ability.ability.name, &self.champ_stats, target));
+        }
 
-        let mut aa_damage = self.calculate_physical_damage_taken_from_aa(effective_armor, &mut _source.champ_stats);
+        Some(ability.ability.damage(ability.rank, &self.champ_stats, target))
+    }
+
+    /// Apply crowd control, shortened by tenacity. A longer effect of the same type replaces a shorter
+    /// one.
+    pub fn apply_crowd_control(&mut self, crowd_control: CrowdControl) {
+        let mut duration = crowd_control.duration;
+
+        if crowd_control.kind.reduced_by_tenacity() {
+            duration *= (1.0 - self.champ_stats.tenacity as f32 / 100.0).max(0.0);
+        }
+
+        let duration = Duration::from_secs_f32(duration);
+        let time_left = self.crowd_control.entry(crowd_control.kind).or_insert(Duration::ZERO);
+
+        *time_left = (*time_left).max(duration);
+    }
+
+    pub fn is_crowd_controlled(&self, kind: CrowdControlType) -> bool {
+        self.crowd_control.contains_key(&kind)
+    }
+
+    /// Whether the champion cannot move, so skillshots always hit it.
+    pub fn is_immobilized(&self) -> bool {
+        self.crowd_control.keys().any(|kind| kind.immobilizes())
+    }
+
+    /// Whether the champion can attack and cast, i.e. it is not stunned or knocked up.
+    pub fn can_act(&self) -> bool {
+        !self.crowd_control.keys().any(|kind| kind.disables())
+    }
+
+    fn tick_crowd_control(&mut self) {
+        for time_left in self.crowd_control.values_mut() {
+            *time_left = time_left.saturating_sub(Duration::from_secs_f32(TICK_SECOND));
+        }
+
+        self.crowd_control.retain(|_, time_left| !time_left.is_zero());
+    }
+
+    fn tick_ability_cooldowns(&mut self) {
+        for ability in self.abilities.values_mut() {
+            ability.tick();
+        }
+    }
+
+    pub fn take_auto_attack_damage(&mut self, _source: &mut Champion, rng: &mut impl Rng) -> Damage {
+        let effective_armor = self.champ_stats.calculate_armor_reduction(&_source.champ_stats);
+        let effective_mr = self.champ_stats.calculate_magic_resist_reduction(&_source.champ_stats);
+
+        let mut aa_damage = self.calculate_physical_damage_taken_from_aa(effective_armor, &_source.champ_stats, &mut _source.crit_state, rng);
 
         let on_hit_damage_pre_mit = self.calculate_on_hit_damage(_source);
-        let mut on_hit_damage = Damage::new(0.0, 0.0, 0.0);
-        on_hit_damage.physical_component = self.calculate_physical_damage_taken(effective_armor, on_hit_damage_pre_mit.physical_component);
-        on_hit_damage.magical_component = self.calculate_magical_damage_taken(effective_mr, on_hit_damage_pre_mit.magical_component);
-        on_hit_damage.true_component = on_hit_damage_pre_mit.true_component;
+        let on_hit_damage = self.calculate_mitigated_damage(effective_armor, effective_mr, on_hit_damage_pre_mit);
 
         aa_damage += on_hit_damage;
 
+        // The damage amp applies from before this hit, so a Press the Attack proc does not amplify itself
+        let damage_amp = _source.keystone.as_ref().map_or(1.0, |keystone| keystone.damage_amp());
+        let keystone_hit = _source.trigger_keystone_on_hit(HitSource::Attack);
+        let keystone_damage = self.calculate_mitigated_damage(effective_armor, effective_mr, keystone_hit.damage) * damage_amp;
+
+        aa_damage = aa_damage * damage_amp + keystone_damage;
+
         self.take_damage(aa_damage);
+        _source.heal(_source.champ_stats.vamp_healing(aa_damage, true, false));
 
         _source.decrement_limited_use_on_hit_effects();
-
-        println!("Remaining health: {}", self.champ_stats.health);
+        _source.champ_stats.gain_resource_on_hit();
+        _source.record_keystone_hit(keystone_damage, keystone_hit.healing, aa_damage.total());
 
         aa_damage
     }
 
+    fn calculate_mitigated_damage(&self, effective_armor: f32, effective_mr: f32, damage: Damage) -> Damage {
+        Damage::new(
+            self.calculate_physical_damage_taken(effective_armor, damage.physical_component),
+            self.calculate_magical_damage_taken(effective_mr, damage.magical_component),
+            damage.true_component,
+        )
+    }
+
+    /// Let the keystone react to one of this champion's attacks or abilities hitting. Returns the
+    /// keystone's bonus damage before mitigation, and its healing.
+    fn trigger_keystone_on_hit(&mut self, source: HitSource) -> KeystoneHit {
+        let max_health = self.max_health();
+
+        match self.keystone.as_mut() {
+            Some(keystone) => {
+                let hit = keystone.on_hit(&mut self.champ_stats, self.level, max_health, source);
+                self.champ_stats.calculate_ad_from_level(self.level);
+                self.champ_stats.calculate_as_from_level(self.level);
+
+                hit
+            }
+            None => KeystoneHit { damage: Damage::new(0.0, 0.0, 0.0), healing: 0.0 },
+        }
+    }
+
+    /// Apply keystone healing for a hit that dealt `damage_dealt` in total, and add the keystone's
+    /// part of the damage and healing to its totals.
+    fn record_keystone_hit(&mut self, keystone_damage: Damage, healing: f32, damage_dealt: f32) {
+        let healing = match &self.keystone {
+            Some(keystone) => healing + keystone.healing_from_damage(&self.champ_stats, damage_dealt),
+            None => return,
+        };
+
+        let healed = self.heal(healing);
+
+        if let Some(keystone) = self.keystone.as_mut() {
+            keystone.damage_dealt += keystone_damage;
+            keystone.healing_done += healed;
+
+            keystone.last_hit = if keystone_damage.total() > 0.0 || healed > 0.0 {
+                Some(KeystoneHit { damage: keystone_damage, healing: healed })
+            } else {
+                None
+            };
+        }
+    }
+
+    /// Advance keystone timers by one tick.
+    fn tick_keystone(&mut self) {
+        if let Some(keystone) = self.keystone.as_mut() {
+            if keystone.tick(&mut self.champ_stats, self.level) {
+                self.champ_stats.calculate_ad_from_level(self.level);
+                self.champ_stats.calculate_as_from_level(self.level);
+            }
+        }
+    }
+
     pub fn apply_enemy_dot_on_hit_effect(&mut self, effect: DoTEffect) {
         let existing_effect = self.enemy_dot_on_hit_effects.get_mut(&effect.id);
 
         match existing_effect {
             Some(existing_effect) => {
-                existing_effect.damage_time_left = effect.damage_time_left;
+                existing_effect.set_damage_time_left(effect.damage_time_left);
+                existing_effect.damage_over_time = effect.damage_over_time;
             }
             None => {
                 self.enemy_dot_on_hit_effects.insert(effect.id.to_string(), effect);
@@ -71,8 +329,11 @@ impl Champion {
         match existing_effect {
             Some(existing_effect) => {
                 if existing_effect.current_stacks < effect.max_stacks {
-                    existing_effect.current_stacks += 1;
+                    existing_effect.set_current_stacks(existing_effect.current_stacks + 1);
                 }
+
+                existing_effect.set_damage_time_left(effect.damage_time_left);
+                existing_effect.damage_over_time = effect.damage_over_time;
             }
             None => {
                 effect.current_stacks = 1;
@@ -81,20 +342,26 @@ impl Champion {
         }
     }
 
+    /// Take the damage of every enemy effect that ticks on `tick`. Stacking effects deal their damage
+    /// once a second for each stack.
     pub fn calculate_and_apply_dot_effects(&mut self, tick: i32) -> Damage {
         let mut total_dot_damage = Damage::new(0.0, 0.0, 0.0);
         let mut dots_to_take = Vec::new();
 
-        for (id, effect) in self.enemy_dot_on_hit_effects.iter_mut() {
+        for effect in self.enemy_dot_on_hit_effects.values() {
             if tick % &effect.tick_rate == 0 {
-                dots_to_take.push(id.to_string());
+                dots_to_take.push((effect.damage_over_time, effect.damage_type.clone(), 1));
             }
+        }
 
-            effect.effect_time_left -= Duration::from_secs_f32(TICK_SECOND);
+        if tick % &EffectTickRate::PerSecond == 0 {
+            for effect in self.enemy_stacking_on_hit_effects.values() {
+                dots_to_take.push((effect.damage_over_time, effect.damage_type.clone(), effect.current_stacks));
+            }
         }
 
-        for id in dots_to_take {
-            total_dot_damage += self.take_dot_damage(&id);
+        for (damage_over_time, damage_type, stacks) in dots_to_take {
+            total_dot_damage += self.take_dot_damage(&damage_over_time, &damage_type, stacks);
         }
 
         // TODO: Apply armor reduction and other stat burn effects
@@ -102,41 +369,44 @@ impl Champion {
         total_dot_damage
     }
 
-     fn take_dot_damage(&mut self, id: &String) -> Damage {
+    fn take_dot_damage(&mut self, damage_over_time: &DamageFormula, damage_type: &DamageType, stacks: i32) -> Damage {
         // TODO: Take into consideration item changes in armor + bonus mr (?)
         let effective_armor = self.champ_stats.armor + self.champ_stats.bonus_armor;
         let effective_mr = self.champ_stats.mr + self.champ_stats.bonus_mr;
 
-        let mut dot_damage = Damage::new(0.0, 0.0, 0.0);
-
-        let effect = self.enemy_dot_on_hit_effects.get(id);
-
-        match effect {
-            Some(effect) => {
-                match effect.damage_type {
-                    DamageType::Physical => {
-                        dot_damage.physical_component = self.calculate_physical_damage_taken(effective_armor, effect.damage_over_time);
-                    }
-                    DamageType::Magical => {
-                        dot_damage.magical_component = self.calculate_magical_damage_taken(effective_mr, effect.damage_over_time);
-                    }
-                    DamageType::True => {
-                        dot_damage.true_component += effect.damage_over_time;
-                    }
-                }
-            }
-            None => {
-                return dot_damage;
-            }
-        }
+        let amount = damage_over_time.target_amount(&self.champ_stats) * stacks as f32;
+        let dot_damage = self.calculate_mitigated_damage(effective_armor, effective_mr, Damage::from_type(damage_type, amount));
 
         self.take_damage(dot_damage);
 
         dot_damage
     }
 
+    /// Grant the on-hit and damage over time effects of `item`, which last for the whole fight.
+    pub fn add_item_effects(&mut self, item: &Item) {
+        if let Some(on_hit) = &item.on_hit {
+            self.add_friendly_limited_use_on_hit_effect(LimitedUseOnHitEffect::new(&item.name, on_hit.damage, on_hit.damage_type.clone(),
+                                                                                   UNLIMITED_USES, Duration::MAX, false));
+        }
+
+        if let Some(dot) = &item.dot {
+            let duration = Duration::from_secs_f32(dot.duration);
+
+            match dot.max_stacks {
+                Some(max_stacks) => {
+                    let effect = StackingOnHitEffect::new(&item.name, dot.damage, dot.damage_type.clone(), max_stacks, duration, Duration::MAX, false);
+                    self.add_friendly_stacking_on_hit_effect(effect.with_trigger(dot.trigger));
+                }
+                None => {
+                    let effect = DoTEffect::new(&item.name, dot.damage, dot.damage_type.clone(), duration, EffectTickRate::PerSecond, Duration::MAX, false);
+                    self.add_friendly_duration_on_hit_effect(effect.with_trigger(dot.trigger));
+                }
+            }
+        }
+    }
+
     pub fn add_friendly_limited_use_on_hit_effect(&mut self, effect: LimitedUseOnHitEffect) {
-        if self.friendly_limited_use_on_hit_effects.get(&effect.id).is_some() {
+        if self.friendly_limited_use_on_hit_effects.contains_key(&effect.id) {
             return;
         }
 
@@ -144,7 +414,7 @@ impl Champion {
     }
 
     pub fn add_friendly_duration_on_hit_effect(&mut self, effect: DoTEffect) {
-        if self.friendly_duration_on_hit_effects.get(&effect.id).is_some() {
+        if self.friendly_duration_on_hit_effects.contains_key(&effect.id) {
             return;
         }
 
@@ -152,7 +422,7 @@ impl Champion {
     }
 
     pub fn add_friendly_stacking_on_hit_effect(&mut self, effect: StackingOnHitEffect) {
-        if self.friendly_stacking_on_hit_effects.get(&effect.id).is_some() {
+        if self.friendly_stacking_on_hit_effects.contains_key(&effect.id) {
             return;
         }
 
@@ -168,20 +438,9 @@ impl Champion {
     fn calculate_on_hit_damage(&mut self, _source: &Champion) -> Damage {
         let mut damage = Damage::new(0.0, 0.0, 0.0);
 
-        for (_, effect) in &_source.friendly_limited_use_on_hit_effects {
+        for effect in _source.friendly_limited_use_on_hit_effects.values() {
             if effect.num_uses > 0 {
-                match effect.damage_type {
-                    DamageType::Physical => {
-                        damage.physical_component += effect.damage;
-                    }
-                    DamageType::Magical => {
-                        damage.magical_component += effect.damage;
-                    }
-                    DamageType::True => {
-                        damage.true_component += effect.damage;
-                    }
-                }
-
+                damage += Damage::from_type(&effect.damage_type, effect.damage.amount(&_source.champ_stats, &self.champ_stats));
             }
         }
 
@@ -189,20 +448,19 @@ impl Champion {
     }
 
     fn decrement_limited_use_on_hit_effects(&mut self) {
-        for (_, effect) in &mut self.friendly_limited_use_on_hit_effects {
-            if effect.num_uses > 0 {
-                effect.num_uses -= 1;
+        for effect in self.friendly_limited_use_on_hit_effects.values_mut() {
+            if effect.num_uses > 0 && effect.num_uses != UNLIMITED_USES {
+                effect.reduce_num_uses();
             }
         }
     }
 
     pub fn decrement_own_effect_time_left(&mut self) {
-        let mut duration_effects_to_remove = Vec::new();
-        let mut stacking_effects_to_remove = Vec::new();
+        let mut effects_to_remove = Vec::new();
 
-        for (id, effect) in self.friendly_duration_on_hit_effects.iter_mut() {
+        for (id, effect) in self.friendly_limited_use_on_hit_effects.iter_mut() {
             if effect.effect_time_left <= Duration::from_secs(0) {
-                duration_effects_to_remove.push(id.to_string());
+                effects_to_remove.push(id.to_string());
                 continue;
             }
 
@@ -211,13 +469,20 @@ impl Champion {
             }
         }
 
-        for id in duration_effects_to_remove {
-            self.friendly_duration_on_hit_effects.remove(&id);
+        for (id, effect) in self.friendly_duration_on_hit_effects.iter_mut() {
+            if effect.effect_time_left <= Duration::from_secs(0) {
+                effects_to_remove.push(id.to_string());
+                continue;
+            }
+
+            if effect.finite_time_left {
+                effect.reduce_effect_time_left(Duration::from_secs_f32(TICK_SECOND));
+            }
         }
 
         for (id, effect) in self.friendly_stacking_on_hit_effects.iter_mut() {
             if effect.effect_time_left <= Duration::from_secs(0) {
-                stacking_effects_to_remove.push(id.to_string());
+                effects_to_remove.push(id.to_string());
                 continue;
             }
 
@@ -226,23 +491,26 @@ impl Champion {
             }
         }
 
-        for id in stacking_effects_to_remove {
-            self.friendly_stacking_on_hit_effects.remove(&id);
+        for id in effects_to_remove {
+            self.remove_on_hit_effect(&id);
         }
+
+        self.tick_keystone();
+        self.tick_ability_cooldowns();
+        self.shields.tick();
+        self.champ_stats.regenerate_resource();
     }
 
+    /// Enemy effects last for their damage time, which reapplying them refreshes.
     pub fn decrement_enemy_effect_time_left(&mut self) {
         let mut duration_effects_to_remove = Vec::new();
         let mut stacking_effects_to_remove = Vec::new();
 
         for (id, effect) in self.enemy_dot_on_hit_effects.iter_mut() {
-            if effect.effect_time_left <= Duration::from_secs(0) {
-                duration_effects_to_remove.push(id.to_string());
-                continue;
-            }
+            effect.reduce_damage_time_left(Duration::from_secs_f32(TICK_SECOND));
 
-            if effect.finite_time_left {
-                effect.reduce_effect_time_left(Duration::from_secs_f32(TICK_SECOND));
+            if effect.damage_time_left.is_zero() {
+                duration_effects_to_remove.push(id.to_string());
             }
         }
 
@@ -251,35 +519,25 @@ impl Champion {
         }
 
         for (id, effect) in self.enemy_stacking_on_hit_effects.iter_mut() {
-            if effect.effect_time_left <= Duration::from_secs(0) {
-                stacking_effects_to_remove.push(id.to_string());
-                continue;
-            }
+            effect.reduce_damage_time_left(Duration::from_secs_f32(TICK_SECOND));
 
-            if effect.finite_time_left {
-                effect.reduce_effect_time_left(Duration::from_secs_f32(TICK_SECOND));
+            if effect.damage_time_left.is_zero() {
+                stacking_effects_to_remove.push(id.to_string());
             }
         }
 
         for id in stacking_effects_to_remove {
             self.enemy_stacking_on_hit_effects.remove(&id);
         }
+
+        self.tick_crowd_control();
     }
 
-    fn calculate_physical_damage_taken_from_aa(&self, effective_armor: f32, _source: &ChampStats) -> Damage {
+    fn calculate_physical_damage_taken_from_aa(&self, effective_armor: f32, _source: &ChampStats, crit_state: &mut CritState, rng: &mut impl Rng) -> Damage {
         let mut damage = Damage::new(_source.ad as f32, 0.0, 0.0);
 
-        // Simplified crit damage calculation; we do not apply smoothing to compensate for "streaks"
         let crit_damage_multiplier = calculate_crit_damage_multiplier_from_target(_source);
-
-        if _source.crit > 0.0 {
-            let mut rng = rand::thread_rng();
-            let probability = rng.gen::<f32>();
-
-            if probability <= _source.crit {
-                damage.physical_component = damage.physical_component * crit_damage_multiplier;
-            }
-        }
+        damage.physical_component *= crit_state.damage_multiplier(_source.crit, crit_damage_multiplier, rng);
 
         damage.physical_component = self.calculate_physical_damage_taken(effective_armor, damage.physical_component);
 
@@ -287,7 +545,7 @@ impl Champion {
     }
 
     fn calculate_physical_damage_taken(&self, effective_armor: f32, damage: f32) -> f32 {
-        return if effective_armor >= 0.0 {
+        if effective_armor >= 0.0 {
             (100.0 / (100.0 + effective_armor)) * damage
         } else {
             (2.0 - 100.0 / (100.0 - effective_armor)) * damage
@@ -295,7 +553,7 @@ impl Champion {
     }
 
     fn calculate_magical_damage_taken(&self, effective_mr: f32, damage: f32) -> f32 {
-        return if effective_mr >= 0.0 {
+        if effective_mr >= 0.0 {
             (100.0 / (100.0 + effective_mr)) * damage
         } else {
             (2.0 - 100.0 / (100.0 - effective_mr)) * damage
@@ -309,120 +567,77 @@ impl Champion {
     }
 
     fn take_physical_damage(&mut self, damage: &mut Damage) {
-        let stats = &mut self.champ_stats;
-        // Take away from the physical shield first
-        if stats.physical_shield_amount > 0.0 {
-            let old_physical_shield_amount = stats.physical_shield_amount;
-
-            stats.physical_shield_amount = stats.physical_shield_amount - damage.physical_component;
-
-            damage.reduce_physical_damage(old_physical_shield_amount);
-
-            if stats.physical_shield_amount < 0.0 {
-                stats.physical_shield_amount = 0.0;
-            }
-        }
-
-        // Take away from the shield second
-        if stats.shield_amount > 0.0 && damage.physical_component > 0.0 {
-            let old_shield_amount = stats.shield_amount;
-
-            stats.shield_amount = stats.shield_amount - damage.physical_component;
-
-            damage.reduce_physical_damage(old_shield_amount);
-
-            if stats.shield_amount < 0.0 {
-                stats.shield_amount = 0.0;
-            }
-        }
+        // Take away from the physical shields first, then from shields against all damage
+        let physical_component = self.shields.absorb(damage.physical_component, ShieldType::Physical);
 
         // Take away from the health last
-        if damage.physical_component > 0.0 {
-            stats.health = (stats.health - damage.physical_component).round();
-
-            if stats.health < 0.0 {
-                stats.health = 0.0;
-            }
-
-            damage.reduce_physical_damage(damage.physical_component);
+        if physical_component > 0.0 {
+            self.champ_stats.lose_health(physical_component);
         }
+
+        damage.reduce_physical_damage(damage.physical_component);
     }
 
     fn take_magical_damage(&mut self, damage: &mut Damage) {
-        let stats = &mut self.champ_stats;
-        // Take away from the magic shield first
-        if stats.magic_shield_amount > 0.0 {
-            let old_magic_shield_amount = stats.magic_shield_amount;
-            stats.magic_shield_amount = stats.magic_shield_amount - damage.magical_component;
-            damage.reduce_magical_damage(old_magic_shield_amount);
-
-            if stats.magic_shield_amount < 0.0 {
-                stats.magic_shield_amount = 0.0;
-            }
-        }
-
-        // Take away from the shield second
-        if stats.shield_amount > 0.0 && damage.magical_component > 0.0 {
-            let old_shield_amount = stats.shield_amount;
-            stats.shield_amount = stats.shield_amount - damage.magical_component;
-            damage.reduce_magical_damage(old_shield_amount);
-
-            if stats.shield_amount < 0.0 {
-                stats.shield_amount = 0.0;
-            }
-        }
+        // Take away from the magic shields first, then from shields against all damage
+        let magical_component = self.shields.absorb(damage.magical_component, ShieldType::Magic);
 
         // Take away from the health last
-        if damage.magical_component > 0.0 {
-            stats.health = (stats.health - damage.magical_component).round();
-            damage.reduce_magical_damage(damage.magical_component);
-
-            if stats.health < 0.0 {
-                stats.health = 0.0;
-            }
+        if magical_component > 0.0 {
+            self.champ_stats.lose_health(magical_component);
         }
+
+        damage.reduce_magical_damage(damage.magical_component);
     }
 
     fn take_true_damage(&mut self, damage: &mut Damage) {
-        let stats = &mut self.champ_stats;
-
-        // Take away from the shield first
-        if stats.shield_amount > 0.0 {
-            let old_shield_amount = stats.shield_amount;
-            stats.shield_amount = stats.shield_amount - damage.true_component;
-            damage.reduce_true_damage(old_shield_amount);
-
-            if stats.shield_amount < 0.0 {
-                stats.shield_amount = 0.0;
-            }
-        }
+        // Only shields against all damage block true damage
+        let true_component = self.shields.absorb(damage.true_component, ShieldType::All);
 
         // Take away from the health last
-        if damage.true_component > 0.0 {
-            stats.health = (stats.health - damage.true_component).round();
-            damage.reduce_true_damage(damage.true_component);
-
-            if stats.health < 0.0 {
-                stats.health = 0.0;
-            }
+        if true_component > 0.0 {
+            self.champ_stats.lose_health(true_component);
         }
+
+        damage.reduce_true_damage(damage.true_component);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
-    use crate::effects::{DamageType, DoTEffect, LimitedUseOnHitEffect, StackingOnHitEffect, EffectTickRate};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::ability::AbilitySlot;
+    use crate::champion::Champion;
+    use crate::champion::stats::ResourceType;
+    use crate::constants::AREA_VAMP_EFFECTIVENESS;
+    use crate::crit::CritModel;
+    use crate::effects::{DamageFormula, DamageType, DoTEffect, LimitedUseOnHitEffect, StackingOnHitEffect, EffectTickRate};
+    use crate::keystone::Keystone;
+    use crate::shield::{Shield, ShieldEffect, ShieldType};
     use crate::utils::create_champion_by_name;
 
+    /// The amount left on `champion`'s shields of exactly `kind`.
+    fn shield_amount(champion: &Champion, kind: ShieldType) -> f32 {
+        champion.shields.shields.iter()
+            .filter(|shield| shield.kind == kind)
+            .map(|shield| shield.amount)
+            .sum()
+    }
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(0)
+    }
+
     #[test]
     fn test_set_level() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         assert_eq!(champion.champ_stats.health, 685.0);
-        assert_eq!(champion.champ_stats.hp5, 3);
-        assert_eq!(champion.champ_stats.resource, 0);
-        assert_eq!(champion.champ_stats.rp5, 0);
+        assert_eq!(champion.champ_stats.hp5, 3.0);
+        assert_eq!(champion.champ_stats.resource, 0.0);
+        assert_eq!(champion.champ_stats.rp5, 0.0);
         assert_eq!(champion.champ_stats.ad, 60);
         assert_eq!(champion.champ_stats.as_, 0.651);
         assert_eq!(champion.champ_stats.armor, 38.0);
@@ -431,138 +646,138 @@ mod tests {
         champion.set_level(2);
 
         assert_eq!(champion.champ_stats.health, 767.0);
-        assert_eq!(champion.champ_stats.hp5, 4);
-        assert_eq!(champion.champ_stats.resource, 0);
-        assert_eq!(champion.champ_stats.rp5, 0);
+        assert_eq!(champion.champ_stats.hp5, 3.72);
+        assert_eq!(champion.champ_stats.resource, 0.0);
+        assert_eq!(champion.champ_stats.rp5, 0.0);
         assert_eq!(champion.champ_stats.ad, 64);
-        assert_eq!(champion.champ_stats.as_, 0.825468063);
+        assert_eq!(champion.champ_stats.as_, 0.662_718);
         assert_eq!(champion.champ_stats.armor, 41.0);
         assert_eq!(champion.champ_stats.mr, 33.0);
     }
 
     #[test]
     fn test_take_true_damage() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
-        champion.champ_stats.shield_amount = 100.0;
-        champion.champ_stats.magic_shield_amount = 100.0;
-        champion.champ_stats.physical_shield_amount = 100.0;
+        champion.shields.add(Shield::new("test", ShieldType::All, 100.0, Duration::from_secs(5)));
+        champion.shields.add(Shield::new("test", ShieldType::Magic, 100.0, Duration::from_secs(5)));
+        champion.shields.add(Shield::new("test", ShieldType::Physical, 100.0, Duration::from_secs(5)));
 
         let mut damage = crate::damage::Damage::new(0.0, 0.0, 100.0);
 
         champion.take_true_damage(&mut damage);
 
-        assert_eq!(champion.champ_stats.shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
         assert_eq!(champion.champ_stats.health, 685.0);
-        assert_eq!(champion.champ_stats.magic_shield_amount, 100.0);
-        assert_eq!(champion.champ_stats.physical_shield_amount, 100.0);
+        assert_eq!(shield_amount(&champion, ShieldType::Magic), 100.0);
+        assert_eq!(shield_amount(&champion, ShieldType::Physical), 100.0);
 
         damage.true_component = 100.0;
 
         champion.take_true_damage(&mut damage);
 
-        assert_eq!(champion.champ_stats.shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
         assert_eq!(champion.champ_stats.health, 585.0);
 
-        champion.champ_stats.shield_amount = 99.0;
+        champion.shields.add(Shield::new("test", ShieldType::All, 99.0, Duration::from_secs(5)));
         damage.true_component = 100.0;
 
         champion.take_true_damage(&mut damage);
 
-        assert_eq!(champion.champ_stats.shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
         assert_eq!(champion.champ_stats.health, 584.0);
 
         damage.true_component = 600.0;
 
         champion.take_true_damage(&mut damage);
 
-        assert_eq!(champion.champ_stats.shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
         assert_eq!(champion.champ_stats.health, 0.0);
     }
 
     #[test]
     fn take_magic_damage() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
-        champion.champ_stats.shield_amount = 100.0;
-        champion.champ_stats.magic_shield_amount = 100.0;
-        champion.champ_stats.physical_shield_amount = 100.0;
+        champion.shields.add(Shield::new("test", ShieldType::All, 100.0, Duration::from_secs(5)));
+        champion.shields.add(Shield::new("test", ShieldType::Magic, 100.0, Duration::from_secs(5)));
+        champion.shields.add(Shield::new("test", ShieldType::Physical, 100.0, Duration::from_secs(5)));
 
         let mut damage = crate::damage::Damage::new(0.0, 100.0, 0.0);
 
         champion.take_magical_damage(&mut damage);
 
-        assert_eq!(champion.champ_stats.shield_amount, 100.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 100.0);
         assert_eq!(champion.champ_stats.health, 685.0);
-        assert_eq!(champion.champ_stats.magic_shield_amount, 0.0);
-        assert_eq!(champion.champ_stats.physical_shield_amount, 100.0);
+        assert_eq!(shield_amount(&champion, ShieldType::Magic), 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::Physical), 100.0);
 
         damage.magical_component = 100.0;
 
         champion.take_magical_damage(&mut damage);
 
-        assert_eq!(champion.champ_stats.shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
         assert_eq!(champion.champ_stats.health, 685.0);
 
-        champion.champ_stats.shield_amount = 99.0;
+        champion.shields.add(Shield::new("test", ShieldType::All, 99.0, Duration::from_secs(5)));
         damage.magical_component = 100.0;
 
         champion.take_magical_damage(&mut damage);
 
-        assert_eq!(champion.champ_stats.shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
         assert_eq!(champion.champ_stats.health, 684.0);
 
         damage.magical_component = 700.0;
 
         champion.take_magical_damage(&mut damage);
 
-        assert_eq!(champion.champ_stats.shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
         assert_eq!(champion.champ_stats.health, 0.0);
     }
 
     #[test]
     fn take_physical_damage() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
-        champion.champ_stats.shield_amount = 100.0;
-        champion.champ_stats.magic_shield_amount = 100.0;
-        champion.champ_stats.physical_shield_amount = 100.0;
+        champion.shields.add(Shield::new("test", ShieldType::All, 100.0, Duration::from_secs(5)));
+        champion.shields.add(Shield::new("test", ShieldType::Magic, 100.0, Duration::from_secs(5)));
+        champion.shields.add(Shield::new("test", ShieldType::Physical, 100.0, Duration::from_secs(5)));
 
         let mut damage = crate::damage::Damage::new(100.0, 0.0, 0.0);
 
         champion.take_physical_damage(&mut damage);
 
-        assert_eq!(champion.champ_stats.shield_amount, 100.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 100.0);
         assert_eq!(champion.champ_stats.health, 685.0);
-        assert_eq!(champion.champ_stats.magic_shield_amount, 100.0);
-        assert_eq!(champion.champ_stats.physical_shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::Magic), 100.0);
+        assert_eq!(shield_amount(&champion, ShieldType::Physical), 0.0);
 
         damage.physical_component = 100.0;
 
         champion.take_physical_damage(&mut damage);
 
-        assert_eq!(champion.champ_stats.shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
         assert_eq!(champion.champ_stats.health, 685.0);
 
-        champion.champ_stats.shield_amount = 99.0;
+        champion.shields.add(Shield::new("test", ShieldType::All, 99.0, Duration::from_secs(5)));
         damage.physical_component = 100.0;
 
         champion.take_physical_damage(&mut damage);
 
-        assert_eq!(champion.champ_stats.shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
         assert_eq!(champion.champ_stats.health, 684.0);
 
         damage.physical_component = 700.0;
 
         champion.take_physical_damage(&mut damage);
 
-        assert_eq!(champion.champ_stats.shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
         assert_eq!(champion.champ_stats.health, 0.0);
     }
 
     #[test]
     fn test_calculate_physical_damage_taken() {
-        let champion = create_champion_by_name("test-bruiser");
+        let champion = create_champion_by_name("test-bruiser").unwrap();
         let effective_armor = 25.0;
         let damage = 1000.0;
 
@@ -583,7 +798,7 @@ mod tests {
 
     #[test]
     fn test_calculate_magic_damage_taken() {
-        let champion = create_champion_by_name("test-bruiser");
+        let champion = create_champion_by_name("test-bruiser").unwrap();
         let effective_mr = 25.0;
         let damage = 1000.0;
 
@@ -604,7 +819,7 @@ mod tests {
 
     #[test]
     fn test_take_damage() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         let damage1 = crate::damage::Damage::new(100.0, 100.0, 100.0);
         let damage2 = crate::damage::Damage::new(100.0, 100.0, 100.0);
@@ -612,25 +827,25 @@ mod tests {
         champion.take_damage(damage1);
 
         assert_eq!(champion.champ_stats.health, 385.0);
-        assert_eq!(champion.champ_stats.shield_amount, 0.0);
-        assert_eq!(champion.champ_stats.magic_shield_amount, 0.0);
-        assert_eq!(champion.champ_stats.physical_shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::Magic), 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::Physical), 0.0);
 
-        champion.champ_stats.shield_amount = 100.0;
-        champion.champ_stats.magic_shield_amount = 100.0;
-        champion.champ_stats.physical_shield_amount = 100.0;
+        champion.shields.add(Shield::new("test", ShieldType::All, 100.0, Duration::from_secs(5)));
+        champion.shields.add(Shield::new("test", ShieldType::Magic, 100.0, Duration::from_secs(5)));
+        champion.shields.add(Shield::new("test", ShieldType::Physical, 100.0, Duration::from_secs(5)));
 
         champion.take_damage(damage2);
 
         assert_eq!(champion.champ_stats.health, 385.0);
-        assert_eq!(champion.champ_stats.shield_amount, 0.0);
-        assert_eq!(champion.champ_stats.magic_shield_amount, 0.0);
-        assert_eq!(champion.champ_stats.physical_shield_amount, 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::Magic), 0.0);
+        assert_eq!(shield_amount(&champion, ShieldType::Physical), 0.0);
     }
 
     #[test]
     fn test_add_limited_use_on_hit_effect() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         let effect = LimitedUseOnHitEffect::new("test", 100.0, DamageType::Physical, 1, Duration::from_secs(10), true);
 
@@ -641,7 +856,7 @@ mod tests {
 
     #[test]
     fn test_add_duplicate_limited_use_on_hit_effect() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         let effect = LimitedUseOnHitEffect::new("test", 100.0, DamageType::Physical, 1, Duration::from_secs(10), true);
         let effect2 = LimitedUseOnHitEffect::new("test", 100.0, DamageType::Physical, 1, Duration::from_secs(10), true);
@@ -654,7 +869,7 @@ mod tests {
 
     #[test]
     fn test_add_duration_on_hit_effect() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         let effect = DoTEffect::new("test", 100.0, DamageType::Physical, Duration::new(2, 0), EffectTickRate::PerSecond, Duration::new(10, 0), true);
 
@@ -665,7 +880,7 @@ mod tests {
 
     #[test]
     fn test_add_duplicate_duration_on_hit_effect() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         let effect = DoTEffect::new("test", 100.0, DamageType::Physical, Duration::new(2, 0), EffectTickRate::PerSecond, Duration::new(10, 0), true);
         let effect2 = DoTEffect::new("test", 100.0, DamageType::Physical, Duration::new(2, 0), EffectTickRate::PerSecond, Duration::new(10, 0), true);
@@ -678,7 +893,7 @@ mod tests {
 
     #[test]
     fn test_add_stacking_on_hit_effect() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         let effect = StackingOnHitEffect::new("test", 5.0, DamageType::Physical, 100, Duration::new(2, 0), Duration::from_secs(10), true);
 
@@ -689,7 +904,7 @@ mod tests {
 
     #[test]
     fn test_add_duplicate_stacking_on_hit_effect() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         let effect = StackingOnHitEffect::new("test", 5.0, DamageType::Physical, 100, Duration::new(2, 0), Duration::from_secs(10), true);
         let effect2 = StackingOnHitEffect::new("test", 5.0, DamageType::Physical, 100, Duration::new(2, 0), Duration::from_secs(10), true);
@@ -702,7 +917,7 @@ mod tests {
 
     #[test]
     fn test_remove_limited_use_on_hit_effects() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         let effect = LimitedUseOnHitEffect::new("test", 100.0, DamageType::Physical, 1, Duration::from_secs(10), true);
         let effect2 = LimitedUseOnHitEffect::new("test2", 100.0, DamageType::Physical, 1, Duration::from_secs(10), true);
@@ -716,13 +931,13 @@ mod tests {
 
         assert_eq!(champion.friendly_limited_use_on_hit_effects.len(), 2);
         assert_eq!(champion.friendly_limited_use_on_hit_effects.get("test2"), None);
-        assert!(champion.friendly_limited_use_on_hit_effects.get("test").is_some());
-        assert!(champion.friendly_limited_use_on_hit_effects.get("test3").is_some());
+        assert!(champion.friendly_limited_use_on_hit_effects.contains_key("test"));
+        assert!(champion.friendly_limited_use_on_hit_effects.contains_key("test3"));
     }
 
     #[test]
     fn test_remove_on_hit_effects_duration() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         let effect = DoTEffect::new("test", 100.0, DamageType::Physical, Duration::new(2, 0), EffectTickRate::PerSecond, Duration::new(10, 0), true);
         let effect2 = DoTEffect::new("test2", 100.0, DamageType::Physical, Duration::new(2, 0), EffectTickRate::PerSecond, Duration::new(10, 0), true);
@@ -736,13 +951,13 @@ mod tests {
 
         assert_eq!(champion.friendly_duration_on_hit_effects.len(), 2);
         assert_eq!(champion.friendly_duration_on_hit_effects.get("test2"), None);
-        assert!(champion.friendly_duration_on_hit_effects.get("test").is_some());
-        assert!(champion.friendly_duration_on_hit_effects.get("test3").is_some());
+        assert!(champion.friendly_duration_on_hit_effects.contains_key("test"));
+        assert!(champion.friendly_duration_on_hit_effects.contains_key("test3"));
     }
 
     #[test]
     fn test_remove_on_hit_effects_stacking() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         let effect = StackingOnHitEffect::new("test", 5.0, DamageType::Physical, 100, Duration::new(2, 0), Duration::from_secs(10), true);
         let effect2 = StackingOnHitEffect::new("test2", 5.0, DamageType::Physical, 100, Duration::new(2, 0), Duration::from_secs(10), true);
@@ -756,13 +971,13 @@ mod tests {
 
         assert_eq!(champion.friendly_stacking_on_hit_effects.len(), 2);
         assert_eq!(champion.friendly_stacking_on_hit_effects.get("test2"), None);
-        assert!(champion.friendly_stacking_on_hit_effects.get("test").is_some());
-        assert!(champion.friendly_stacking_on_hit_effects.get("test3").is_some());
+        assert!(champion.friendly_stacking_on_hit_effects.contains_key("test"));
+        assert!(champion.friendly_stacking_on_hit_effects.contains_key("test3"));
     }
 
     #[test]
     fn test_decrement_limited_use_on_hit_effects() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         let effect = LimitedUseOnHitEffect::new("test", 100.0, DamageType::Physical, 1, Duration::from_secs(10), true);
         let effect2 = LimitedUseOnHitEffect::new("test2", 100.0, DamageType::Physical, 2, Duration::from_secs(10), true);
@@ -782,8 +997,8 @@ mod tests {
 
     #[test]
     fn test_calculate_on_hit_damage() {
-        let mut champion = create_champion_by_name("test-bruiser");
-        let mut source = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
 
         let effect = LimitedUseOnHitEffect::new("test", 100.0, DamageType::Physical, 1, Duration::from_secs(10), true);
         let effect2 = LimitedUseOnHitEffect::new("test2", 100.0, DamageType::Physical, 2, Duration::from_secs(10), true);
@@ -806,55 +1021,208 @@ mod tests {
 
     #[test]
     fn test_calculate_physical_damage_taken_from_aa_no_armor() {
-        let champion = create_champion_by_name("test-bruiser");
-        let source = create_champion_by_name("test-bruiser");
+        let champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
 
-        let damage = champion.calculate_physical_damage_taken_from_aa(0.0, &source.champ_stats);
+        let damage = champion.calculate_physical_damage_taken_from_aa(0.0, &source.champ_stats, &mut source.crit_state, &mut rng());
 
         assert_eq!(damage.physical_component, 60.0);
     }
 
     #[test]
     fn test_calculate_physical_damage_taken_from_aa_with_armor() {
-        let champion = create_champion_by_name("test-bruiser");
-        let source = create_champion_by_name("test-bruiser");
+        let champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
 
-        let damage = champion.calculate_physical_damage_taken_from_aa(100.0, &source.champ_stats);
+        let damage = champion.calculate_physical_damage_taken_from_aa(100.0, &source.champ_stats, &mut source.crit_state, &mut rng());
 
         assert_eq!(damage.physical_component, 30.0);
     }
 
     #[test]
     fn test_calculate_physical_damage_taken_from_aa_crit() {
-        let champion = create_champion_by_name("test-bruiser");
-        let mut source = create_champion_by_name("test-bruiser");
+        let champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
 
         source.champ_stats.crit = 1.0;
 
-        let damage = champion.calculate_physical_damage_taken_from_aa(0.0, &source.champ_stats);
+        let damage = champion.calculate_physical_damage_taken_from_aa(0.0, &source.champ_stats, &mut source.crit_state, &mut rng());
 
         assert_eq!(damage.physical_component, 105.0);
     }
 
+    #[test]
+    fn test_expected_crit_model() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
+
+        source.champ_stats.crit = 0.5;
+        source.set_crit_model(CritModel::Expected);
+
+        // Half of the 1.375 crit multiplier's bonus on every attack
+        let damage = champion.take_auto_attack_damage(&mut source, &mut rng());
+        let again = champion.take_auto_attack_damage(&mut source, &mut rng());
+
+        assert_eq!(damage.total(), again.total());
+        assert_eq!(champion.calculate_physical_damage_taken_from_aa(0.0, &source.champ_stats, &mut source.crit_state, &mut rng()).physical_component, 60.0 * 1.1875);
+    }
+
     #[test]
     fn test_take_auto_attack_damage() {
-        let mut champion = create_champion_by_name("test-bruiser");
-        let mut source = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
 
         source.champ_stats.ad = 100;
         source.champ_stats.crit = 1.0;
 
-        champion.take_auto_attack_damage(&mut source);
+        champion.take_auto_attack_damage(&mut source, &mut rng());
 
         assert_eq!(champion.champ_stats.health, 558.0);
     }
+
+    #[test]
+    fn test_life_steal_heals_from_auto_attacks() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
+
+        source.champ_stats.ad = 100;
+        source.champ_stats.crit = 1.0;
+        source.champ_stats.life_steal = 0.5;
+        source.champ_stats.health -= 100.0;
+
+        let damage = champion.take_auto_attack_damage(&mut source, &mut rng());
+
+        assert_eq!(source.healing_done, 0.5 * damage.total());
+        assert_eq!(source.champ_stats.health, source.max_health() - 100.0 + 0.5 * damage.total());
+
+        // Capped at max health
+        let missing_health = source.champ_stats.missing_health();
+        champion.take_auto_attack_damage(&mut source, &mut rng());
+
+        assert_eq!(source.healing_done, 0.5 * damage.total() + missing_health);
+        assert_eq!(source.champ_stats.health, source.max_health());
+    }
+
+    #[test]
+    fn test_ability_healing_and_omnivamp() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
+
+        source.set_ability_rank(AbilitySlot::Q, 1);
+        let ability = &mut source.abilities.get_mut(&AbilitySlot::Q).unwrap().ability;
+        ability.healing = Some(DamageFormula::flat(30.0));
+        ability.area = true;
+        source.champ_stats.omnivamp = 0.75;
+        source.champ_stats.health = 1.0;
+
+        let damage = champion.take_ability_damage(&mut source, AbilitySlot::Q).unwrap();
+
+        assert!(damage.total() > 0.0);
+        assert_eq!(source.healing_done, 30.0 + 0.75 * AREA_VAMP_EFFECTIVENESS * damage.total());
+
+        // Regeneration is not counted as healing
+        source.regenerate_health();
+        assert_eq!(source.healing_done, 30.0 + 0.75 * AREA_VAMP_EFFECTIVENESS * damage.total());
+    }
+
+    #[test]
+    fn test_abilities_trigger_electrocute() {
+        let mut champion = create_champion_by_name("dummy").unwrap();
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
+
+        source.set_ability_rank(AbilitySlot::Q, 1);
+        source.set_ability_rank(AbilitySlot::W, 1);
+        source.set_keystone(Keystone::Electrocute);
+
+        let q = champion.take_ability_damage(&mut source, AbilitySlot::Q).unwrap();
+        let w = champion.take_ability_damage(&mut source, AbilitySlot::W).unwrap();
+
+        assert_eq!(source.keystone.as_ref().unwrap().damage_dealt.total(), 0.0);
+
+        // The attack is the third separate hit, and its damage includes the proc
+        let attack = champion.take_auto_attack_damage(&mut source, &mut rng());
+        let keystone = source.keystone.as_ref().unwrap();
+
+        assert!(keystone.damage_dealt.total() > 0.0);
+        assert_eq!(keystone.last_hit.unwrap().damage.total(), keystone.damage_dealt.total());
+        assert!(attack.total() > keystone.damage_dealt.total());
+        assert!(q.total() > 0.0 && w.total() > 0.0);
+    }
+
+    #[test]
+    fn test_ability_shield_absorbs_and_expires() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let target = create_champion_by_name("dummy").unwrap();
+
+        champion.set_ability_rank(AbilitySlot::Q, 1);
+        champion.abilities.get_mut(&AbilitySlot::Q).unwrap().ability.shield = Some(ShieldEffect {
+            kind: ShieldType::All,
+            amount: DamageFormula::flat(100.0),
+            duration: 1.0,
+            decay: None,
+        });
+
+        champion.cast_ability(AbilitySlot::Q, &target.champ_stats);
+        champion.take_damage(crate::damage::Damage::new(30.0, 0.0, 0.0));
+
+        let name = champion.abilities[&AbilitySlot::Q].ability.name.clone();
+        assert_eq!(champion.shields.absorbed[&name], 30.0);
+        assert_eq!(shield_amount(&champion, ShieldType::All), 70.0);
+        assert_eq!(champion.champ_stats.health, 685.0);
+
+        for _ in 0..30 {
+            champion.decrement_own_effect_time_left();
+        }
+
+        assert_eq!(shield_amount(&champion, ShieldType::All), 0.0);
+        assert!(champion.shields.shields.is_empty());
+    }
+
+    #[test]
+    fn test_regenerate_health() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        champion.champ_stats.hp5 = 10.0;
+        champion.champ_stats.health -= 5.0;
+
+        // Half a second of 10 health per 5 seconds
+        assert_eq!(champion.regenerate_health(), 1.0);
+        assert_eq!(champion.regenerate_health(), 1.0);
+
+        champion.champ_stats.health = champion.max_health() - 0.5;
+        assert_eq!(champion.regenerate_health(), 0.5);
+        assert_eq!(champion.regenerate_health(), 0.0);
+
+        champion.champ_stats.health = 0.0;
+        assert_eq!(champion.regenerate_health(), 0.0);
+    }
+
+    #[test]
+    fn test_cast_ability_spends_mana() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        champion.champ_stats.resource_type = ResourceType::Mana;
+        champion.champ_stats.base_resource = 100.0;
+        champion.set_level(1);
+        champion.set_ability_rank(AbilitySlot::Q, 1);
+        champion.abilities.get_mut(&AbilitySlot::Q).unwrap().ability.cost = vec![60.0];
+        let target = champion.champ_stats.clone();
+
+        assert!(champion.cast_ability(AbilitySlot::Q, &target).is_some());
+        assert_eq!(champion.champ_stats.resource, 40.0);
+
+        champion.abilities.get_mut(&AbilitySlot::Q).unwrap().cooldown_left = Duration::ZERO;
+
+        // Off cooldown, but out of mana
+        assert!(!champion.can_cast(AbilitySlot::Q));
+        assert!(champion.cast_ability(AbilitySlot::Q, &target).is_none());
+        assert_eq!(champion.abilities[&AbilitySlot::Q].casts, 1);
+    }
 }
 
 #[cfg(test)]
 mod struct_tests {
     #[test]
     fn test_clone_champion() {
-        let champion = crate::utils::create_champion_by_name("test-bruiser");
+        let champion = crate::utils::create_champion_by_name("test-bruiser").unwrap();
 
         let clone = champion.clone();
 
@@ -868,13 +1236,13 @@ mod struct_tests {
 #[cfg(test)]
 mod effect_tests {
     use std::time::Duration;
-    use crate::effects::{DamageType, StackingOnHitEffect};
+    use crate::effects::{CrowdControl, CrowdControlType, DamageType, DoTEffect, EffectTickRate, EffectTrigger, StackingOnHitEffect, UNLIMITED_USES};
+    use crate::item::Item;
     use crate::utils::create_champion_by_name;
 
     #[test]
     fn test_apply_enemy_stacking_on_hit_effect() {
-        let mut champion = create_champion_by_name("test-bruiser");
-        let enemy = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         let effect = StackingOnHitEffect::new("test", 10.0, DamageType::Physical, 3, Duration::from_secs(5), Duration::from_secs(10), true);
 
@@ -902,4 +1270,118 @@ mod effect_tests {
         assert_eq!(champion.enemy_stacking_on_hit_effects.len(), 3);
         assert_eq!(champion.enemy_stacking_on_hit_effects.get("test3").unwrap().current_stacks, 1);
     }
+
+    #[test]
+    fn test_stacking_effects_deal_damage_per_stack() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let health = champion.champ_stats.health;
+
+        let effect = StackingOnHitEffect::new("test", 10.0, DamageType::True, 3, Duration::from_secs(2), Duration::from_secs(10), true);
+        champion.apply_enemy_stacking_on_hit_effect(effect.clone());
+        champion.apply_enemy_stacking_on_hit_effect(effect);
+
+        assert_eq!(champion.calculate_and_apply_dot_effects(30).true_component, 20.0);
+        assert_eq!(champion.calculate_and_apply_dot_effects(31).total(), 0.0);
+        assert_eq!(champion.champ_stats.health, health - 20.0);
+
+        // Stacks fall off together once the damage time runs out
+        for _ in 0..60 {
+            champion.decrement_enemy_effect_time_left();
+        }
+
+        assert!(champion.enemy_stacking_on_hit_effects.is_empty());
+    }
+
+    #[test]
+    fn test_enemy_dot_expires_and_refreshes() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let effect = DoTEffect::new("burn", 5.0, DamageType::True, Duration::from_secs(1), EffectTickRate::PerSecond, Duration::from_secs(1), true);
+
+        champion.apply_enemy_dot_on_hit_effect(effect.clone());
+
+        for _ in 0..20 {
+            champion.decrement_enemy_effect_time_left();
+        }
+
+        champion.apply_enemy_dot_on_hit_effect(effect);
+
+        for _ in 0..20 {
+            champion.decrement_enemy_effect_time_left();
+        }
+
+        assert_eq!(champion.calculate_and_apply_dot_effects(60).true_component, 5.0);
+
+        for _ in 0..10 {
+            champion.decrement_enemy_effect_time_left();
+        }
+
+        assert!(champion.enemy_dot_on_hit_effects.is_empty());
+    }
+
+    #[test]
+    fn test_add_item_effects() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+
+        let botrk: Item = serde_json::from_str(r#"{ "id": 3153, "name": "Blade of The Ruined King", "cost": 3200,
+            "on_hit": { "damage": { "target_current_health": 0.09 }, "damage_type": "physical" } }"#).unwrap();
+        let liandrys: Item = serde_json::from_str(r#"{ "id": 6653, "name": "Liandry's Torment", "cost": 3000,
+            "dot": { "trigger": "ability", "damage": { "target_max_health": 0.02 }, "damage_type": "magical", "duration": 3.0 } }"#).unwrap();
+        let stacking: Item = serde_json::from_str(r#"{ "id": 1, "name": "Stacking", "cost": 0,
+            "dot": { "damage": { "flat": 5.0 }, "damage_type": "true", "duration": 4.0, "max_stacks": 5 } }"#).unwrap();
+
+        champion.add_item_effects(&botrk);
+        champion.add_item_effects(&liandrys);
+        champion.add_item_effects(&stacking);
+
+        let on_hit = &champion.friendly_limited_use_on_hit_effects["Blade of The Ruined King"];
+        assert_eq!(on_hit.num_uses, UNLIMITED_USES);
+        assert_eq!(on_hit.damage_type, DamageType::Physical);
+
+        let burn = &champion.friendly_duration_on_hit_effects["Liandry's Torment"];
+        assert_eq!(burn.trigger, EffectTrigger::Ability);
+        assert_eq!(burn.damage_time_left, Duration::from_secs(3));
+
+        let stacks = &champion.friendly_stacking_on_hit_effects["Stacking"];
+        assert_eq!(stacks.trigger, EffectTrigger::Attack);
+        assert_eq!(stacks.max_stacks, 5);
+
+        // Item effects last for the whole fight, and on-hit damage is never used up
+        champion.decrement_limited_use_on_hit_effects();
+
+        for _ in 0..300 {
+            champion.decrement_own_effect_time_left();
+        }
+
+        assert_eq!(champion.friendly_limited_use_on_hit_effects["Blade of The Ruined King"].num_uses, UNLIMITED_USES);
+        assert_eq!(champion.friendly_duration_on_hit_effects.len(), 1);
+        assert_eq!(champion.friendly_stacking_on_hit_effects.len(), 1);
+    }
+
+    #[test]
+    fn test_limited_use_on_hit_effects_expire() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        champion.add_friendly_limited_use_on_hit_effect(crate::effects::LimitedUseOnHitEffect::new("test", 100.0, DamageType::Physical, 3, Duration::from_secs(1), true));
+
+        for _ in 0..31 {
+            champion.decrement_own_effect_time_left();
+        }
+
+        assert!(champion.friendly_limited_use_on_hit_effects.is_empty());
+    }
+
+    #[test]
+    fn test_apply_crowd_control() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        champion.champ_stats.tenacity = 50;
+
+        champion.apply_crowd_control(CrowdControl { kind: CrowdControlType::Stun, duration: 2.0 });
+        champion.apply_crowd_control(CrowdControl { kind: CrowdControlType::Stun, duration: 1.0 });
+        champion.apply_crowd_control(CrowdControl { kind: CrowdControlType::KnockUp, duration: 0.5 });
+
+        // Tenacity halves the stun, but not the knock-up, and the shorter stun does not replace the longer one
+        assert_eq!(champion.crowd_control[&CrowdControlType::Stun], Duration::from_secs(1));
+        assert_eq!(champion.crowd_control[&CrowdControlType::KnockUp], Duration::from_millis(500));
+        assert!(champion.is_immobilized());
+        assert!(!champion.can_act());
+    }
 }
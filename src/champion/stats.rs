@@ -1,44 +1,76 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::constants::{AREA_VAMP_EFFECTIVENESS, ATTACK_SPEED_CAP, TICKS_PER_SECOND};
+use crate::damage::Damage;
+use crate::item::Item;
+
+const MELEE_RANGE: i32 = 300; // Champions with a shorter base attack range are melee
+
+/// What a champion's abilities cost, and how the resource comes back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceType {
+    #[default]
+    None, // Abilities are free
+    Mana,
+    Energy,
+    Fury, // Starts empty and builds with each auto attack instead of regenerating
+    Health, // Abilities cost health, so the resource stats are unused
+}
+
 #[derive(Clone)]
 pub struct ChampStats {
     pub(crate) base_health: f32,
     pub(crate) base_health_growth: f32,
-    pub(crate) health: f32,
+    pub(crate) max_health: f32,
+    pub(crate) health: f32, // Current health; goes down with damage
+    pub(crate) bonus_health: f32,
     pub(crate) base_hp5: f32,
     pub(crate) base_hp5_growth: f32,
-    pub(crate) hp5: i32,
+    pub(crate) hp5: f32,
+    pub(crate) bonus_hp5: f32,
+    pub(crate) bonus_hp5_percent: f32, // Increase of the base regeneration as a fraction, e.g. 1.0 for 100%
     pub(crate) base_resource: f32,
     pub(crate) base_resource_growth: f32,
-    pub(crate) resource: i32,
+    pub(crate) resource_type: ResourceType,
+    pub(crate) max_resource: f32,
+    pub(crate) resource: f32, // Current resource; goes down with casts
+    pub(crate) bonus_resource: f32,
     pub(crate) base_rp5: f32,
     pub(crate) base_rp5_growth: f32,
-    pub(crate) rp5: i32,
+    pub(crate) rp5: f32,
+    pub(crate) bonus_rp5: f32,
+    pub(crate) resource_per_hit: f32, // Fury gained per auto attack
     pub(crate) base_ad: f32,
     pub(crate) base_ad_growth: f32,
-    pub(crate) ad: i32,
+    pub(crate) ad: i32, // Total AD, including bonus_ad
+    pub(crate) bonus_ad: f32,
     pub(crate) base_as: f32,
     pub(crate) base_as_growth_percent: f32,
-    pub(crate) attack_windup: f32,
-    pub(crate) as_: f32, // TODO: Consider the attack speed cap / exceeding the cap
+    pub(crate) attack_windup: f32, // Part of the time between attacks before the damage lands
+    pub(crate) as_: f32, // At most ATTACK_SPEED_CAP, unless attack_speed_uncapped is set
+    pub(crate) attack_speed_uncapped: bool, // Set by effects that let attack speed exceed the cap
     pub(crate) as_ratio: f32,
+    pub(crate) bonus_as: f32, // As a fraction, scaled by as_ratio
+    pub(crate) ap: f32, // All AP is bonus AP
     pub(crate) base_armor: f32,
     pub(crate) base_armor_growth: f32,
-    pub(crate) armor: f32,
+    pub(crate) armor: f32, // Base armor only; bonus_armor is kept separate for bonus armor pen
     pub(crate) bonus_armor: f32,
     pub(crate) base_mr: f32,
     pub(crate) base_mr_growth: f32,
-    pub(crate) mr: f32,
+    pub(crate) mr: f32, // Base MR only
     pub(crate) bonus_mr: f32,
     pub(crate) base_range: i32,
     pub(crate) range: i32,
+    pub(crate) bonus_range: i32,
     pub(crate) base_ms: i32,
     pub(crate) ms: i32,
+    pub(crate) bonus_ms: i32,
+    #[allow(dead_code)]
     pub(crate) base_crit: f32,
     pub(crate) crit: f32,
     pub(crate) bonus_crit_percent: f32,
-    // TODO: Shields only last for a certain amount of time, and some decay! Use a struct
-    pub(crate) shield_amount: f32,
-    pub(crate) magic_shield_amount: f32,
-    pub(crate) physical_shield_amount: f32,
     pub(crate) mr_pen: f32,
     pub(crate) flat_mr_reduction: f32,
     pub(crate) percent_mr_reduction: f32,
@@ -47,23 +79,171 @@ pub struct ChampStats {
     pub(crate) percent_bonus_armor_pen: f32,
     pub(crate) armor_reduction: f32,
     pub(crate) percent_armor_reduction: f32,
-    pub(crate) life_steal: i32,
-    pub(crate) spell_vamp: i32,
+    pub(crate) life_steal: f32, // Fractions of the post-mitigation damage dealt that heal, e.g. 0.08 for 8%
+    pub(crate) omnivamp: f32,
+    pub(crate) physical_vamp: f32,
     pub(crate) tenacity: i32,
+    pub(crate) ability_haste: f32,
+    pub(crate) ultimate_haste: f32, // Only for the R ability, on top of ability_haste
 }
 
 impl ChampStats {
+    /// Recalculate the final stats for `level`: the base stats at that level plus the bonus stats.
+    /// Armor and MR are the exception and only hold the base value, as their bonus part is needed
+    /// separately by armor and MR penetration.
     pub fn calculate_stats_from_level(&mut self, level: i32) {
-        self.health = calculate_base_stat(self.base_health, 0.0, self.base_health_growth, level).round();
-        self.hp5 = calculate_base_stat(self.base_hp5, 0.0, self.base_hp5_growth, level).round() as i32;
-        self.resource = calculate_base_stat(self.base_resource, 0.0, self.base_resource_growth, level).round() as i32;
-        self.rp5 = calculate_base_stat(self.base_rp5, 0.0, self.base_rp5_growth, level).round() as i32;
-        self.ad = calculate_base_stat(self.base_ad, 0.0, self.base_ad_growth, level).round() as i32;
-        self.as_ = calculate_attack_speed(self.base_as, self.as_ratio, self.base_as_growth_percent, 0.25, level);
-        self.armor = calculate_base_stat(self.base_armor, self.bonus_armor, self.base_armor_growth, level).round();
-        self.mr = calculate_base_stat(self.base_mr, self.bonus_mr, self.base_mr_growth, level).round();
+        self.max_health = calculate_base_stat(self.base_health, self.bonus_health, self.base_health_growth, level).round();
+        self.health = self.max_health;
+        self.hp5 = calculate_base_stat(self.base_hp5, 0.0, self.base_hp5_growth, level) * (1.0 + self.bonus_hp5_percent) + self.bonus_hp5;
+        self.max_resource = calculate_base_stat(self.base_resource, self.bonus_resource, self.base_resource_growth, level).round();
+        self.resource = match self.resource_type {
+            ResourceType::Fury => 0.0,
+            _ => self.max_resource,
+        };
+        self.rp5 = calculate_base_stat(self.base_rp5, self.bonus_rp5, self.base_rp5_growth, level);
+        self.calculate_ad_from_level(level);
+        self.calculate_as_from_level(level);
+        self.armor = calculate_base_stat(self.base_armor, 0.0, self.base_armor_growth, level).round();
+        self.mr = calculate_base_stat(self.base_mr, 0.0, self.base_mr_growth, level).round();
+        self.range = self.base_range + self.bonus_range;
+        self.ms = self.base_ms + self.bonus_ms;
+    }
+
+    /// Recalculate only the total AD, e.g. after a mid-fight change to `bonus_ad`.
+    pub fn calculate_ad_from_level(&mut self, level: i32) {
+        self.ad = calculate_base_stat(self.base_ad, self.bonus_ad, self.base_ad_growth, level).round() as i32;
+    }
+
+    /// Recalculate only the total attack speed, e.g. after a mid-fight change to `bonus_as`.
+    pub fn calculate_as_from_level(&mut self, level: i32) {
+        let attack_speed = calculate_attack_speed(self.base_as, self.as_ratio, self.base_as_growth_percent, self.bonus_as, level);
+
+        self.as_ = if self.attack_speed_uncapped { attack_speed } else { attack_speed.min(ATTACK_SPEED_CAP) };
+    }
+
+    pub fn missing_health(&self) -> f32 {
+        (self.max_health - self.health).max(0.0)
+    }
+
+    /// Current health as a fraction of max health, from 0 to 1.
+    pub fn health_percent(&self) -> f32 {
+        if self.max_health <= 0.0 {
+            return 0.0;
+        }
 
-        // TODO: Bonus values from items, runes, etc.
+        self.health / self.max_health
+    }
+
+    /// The part of a heal of `amount` that would go over max health.
+    pub fn overheal(&self, amount: f32) -> f32 {
+        (amount - self.missing_health()).max(0.0)
+    }
+
+    /// Lose health from damage that got through shields, rounded like in the game and never below 0.
+    pub fn lose_health(&mut self, amount: f32) {
+        self.health = (self.health - amount).round().max(0.0);
+    }
+
+    /// Raise max health mid-fight, e.g. from Grasp of the Undying. Current health goes up by the
+    /// same amount.
+    pub fn add_max_health(&mut self, amount: f32) {
+        self.bonus_health += amount;
+        self.max_health += amount;
+        self.health += amount;
+    }
+
+    pub fn missing_resource(&self) -> f32 {
+        (self.max_resource - self.resource).max(0.0)
+    }
+
+    /// Whether an ability costing `cost` can be cast. Health costs cannot be paid with the last
+    /// point of health.
+    pub fn can_pay(&self, cost: f32) -> bool {
+        match self.resource_type {
+            ResourceType::None => true,
+            ResourceType::Health => self.health > cost,
+            _ => self.resource >= cost,
+        }
+    }
+
+    pub fn pay(&mut self, cost: f32) {
+        match self.resource_type {
+            ResourceType::None => {}
+            ResourceType::Health => self.lose_health(cost),
+            _ => self.resource -= cost,
+        }
+    }
+
+    /// Regenerate one tick's worth of `rp5`, up to max resource. Fury does not regenerate.
+    pub fn regenerate_resource(&mut self) {
+        if matches!(self.resource_type, ResourceType::Mana | ResourceType::Energy) {
+            self.gain_resource(self.rp5 / 5.0 / TICKS_PER_SECOND);
+        }
+    }
+
+    /// Build fury from an auto attack. Other resources do not change on hit.
+    pub fn gain_resource_on_hit(&mut self) {
+        if self.resource_type == ResourceType::Fury {
+            self.gain_resource(self.resource_per_hit);
+        }
+    }
+
+    fn gain_resource(&mut self, amount: f32) {
+        self.resource += amount.min(self.missing_resource());
+    }
+
+    pub fn is_melee(&self) -> bool {
+        self.base_range < MELEE_RANGE
+    }
+
+    /// Add an item's stats to the bonus stats. Takes effect on the next `calculate_stats_from_level`.
+    pub fn add_item_stats(&mut self, item: &Item) {
+        self.bonus_health += item.health as f32;
+        self.bonus_hp5 += item.hp5 as f32;
+        self.bonus_hp5_percent += item.hp5_percent as f32 / 100.0;
+        self.bonus_resource += item.mana as f32;
+        self.bonus_rp5 += item.mp5 as f32;
+        self.bonus_ad += item.ad as f32;
+        self.ap += item.ap as f32;
+        self.bonus_as += item.as_;
+        self.bonus_armor += item.armor as f32;
+        self.bonus_mr += item.mr as f32;
+        self.bonus_range += item.range;
+        self.bonus_ms += item.ms;
+        self.add_tenacity(item.tenacity);
+        self.ability_haste += item.ability_haste as f32;
+        self.ultimate_haste += item.ultimate_haste as f32;
+        self.life_steal += item.life_steal;
+        self.omnivamp += item.omnivamp;
+        self.physical_vamp += item.physical_vamp;
+    }
+
+    /// Healing from dealing `damage` after mitigation. Life steal only counts for auto attacks and
+    /// physical vamp only for physical damage, while omnivamp heals from everything but is less
+    /// effective for `area` damage.
+    pub fn vamp_healing(&self, damage: Damage, auto_attack: bool, area: bool) -> f32 {
+        let mut healing = self.physical_vamp * damage.physical_component;
+
+        if auto_attack {
+            healing += self.life_steal * damage.total();
+        }
+
+        let omnivamp = if area { self.omnivamp * AREA_VAMP_EFFECTIVENESS } else { self.omnivamp };
+
+        healing + omnivamp * damage.total()
+    }
+
+    /// Tenacity from different sources stacks multiplicatively.
+    pub fn add_tenacity(&mut self, tenacity: i32) {
+        self.tenacity = 100 - (100 - self.tenacity) * (100 - tenacity) / 100;
+    }
+
+    /// The factor cooldowns are multiplied by, `100 / (100 + ability haste)`. Ultimate haste only
+    /// counts for the ultimate.
+    pub fn cooldown_multiplier(&self, ultimate: bool) -> f32 {
+        let haste = if ultimate { self.ability_haste + self.ultimate_haste } else { self.ability_haste };
+
+        100.0 / (100.0 + haste.max(0.0))
     }
 
     pub fn calculate_armor_reduction(&self, _source: &ChampStats) -> f32 {
@@ -81,14 +261,14 @@ impl ChampStats {
         let armor_reduced = _source.armor_reduction * starting_prop;
         let armor_reduced_bonus = _source.armor_reduction * bonus_prop;
 
-        armor = armor - armor_reduced;
-        bonus_armor = bonus_armor - armor_reduced_bonus;
+        armor -= armor_reduced;
+        bonus_armor -= armor_reduced_bonus;
 
         if armor + bonus_armor > 0.0 {
-            armor = armor * (1.0 - _source.percent_armor_reduction);
-            bonus_armor = bonus_armor * (1.0 - _source.percent_armor_reduction);
+            armor *= 1.0 - _source.percent_armor_reduction;
+            bonus_armor *= 1.0 - _source.percent_armor_reduction;
 
-            bonus_armor = bonus_armor * (1.0 - _source.percent_bonus_armor_pen);
+            bonus_armor *= 1.0 - _source.percent_bonus_armor_pen;
 
             let total_armor = armor + bonus_armor;
 
@@ -110,18 +290,32 @@ impl ChampStats {
             return 0.0;
         }
 
-        mr = mr - _source.flat_mr_reduction;
+        mr -= _source.flat_mr_reduction;
 
         if mr > 0.0 {
-            mr = mr * (1.0 - _source.percent_mr_reduction);
-            mr = mr * (1.0 - _source.percent_mr_pen);
-            mr = mr - _source.mr_pen;
+            mr *= 1.0 - _source.percent_mr_reduction;
+            mr *= 1.0 - _source.percent_mr_pen;
+            mr -= _source.mr_pen;
         }
 
         mr
     }
 }
 
+impl fmt::Display for ResourceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ResourceType::None => "no resource",
+            ResourceType::Mana => "mana",
+            ResourceType::Energy => "energy",
+            ResourceType::Fury => "fury",
+            ResourceType::Health => "health",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
 fn calculate_attack_speed(base_as: f32, as_ratio: f32, growth: f32, bonus_as: f32, n: i32) -> f32 {
     base_as + ((bonus_as + growth * (n - 1) as f32 * (0.7025 + 0.0175 * (n - 1) as f32)) * as_ratio)
 }
@@ -136,7 +330,9 @@ pub fn calculate_crit_damage_multiplier_from_target(_source: &ChampStats) -> f32
 
 #[cfg(test)]
 mod tests {
-    use crate::champion::stats::{calculate_crit_damage_multiplier_from_target, ChampStats};
+    use crate::champion::stats::{calculate_crit_damage_multiplier_from_target, ChampStats, ResourceType};
+    use crate::constants::{AREA_VAMP_EFFECTIVENESS, TICKS_PER_SECOND};
+    use crate::damage::Damage;
     use crate::utils::create_champion_by_name;
 
     #[test]
@@ -144,24 +340,37 @@ mod tests {
         let stats = ChampStats {
             base_health: 1000.0,
             base_health_growth: 100.0,
+            max_health: 1000.0,
             health: 1000.0,
+            bonus_health: 0.0,
             base_hp5: 10.0,
             base_hp5_growth: 1.0,
-            hp5: 10,
+            hp5: 10.0,
+            bonus_hp5: 0.0,
+            bonus_hp5_percent: 0.0,
             base_resource: 1000.0,
             base_resource_growth: 100.0,
-            resource: 1000,
+            resource_type: ResourceType::Mana,
+            max_resource: 1000.0,
+            resource: 1000.0,
+            bonus_resource: 0.0,
             base_rp5: 10.0,
             base_rp5_growth: 1.0,
-            rp5: 10,
+            rp5: 10.0,
+            bonus_rp5: 0.0,
+            resource_per_hit: 0.0,
             base_ad: 100.0,
             base_ad_growth: 10.0,
             ad: 100,
+            bonus_ad: 0.0,
             base_as: 1.0,
             base_as_growth_percent: 0.1,
             attack_windup: 0.25,
+            attack_speed_uncapped: false,
             as_: 1.0,
             as_ratio: 0.1,
+            bonus_as: 0.0,
+            ap: 0.0,
             base_armor: 50.0,
             base_armor_growth: 5.0,
             armor: 50.0,
@@ -172,14 +381,13 @@ mod tests {
             bonus_mr: 0.0,
             base_range: 125,
             range: 125,
+            bonus_range: 0,
             base_ms: 325,
             ms: 325,
+            bonus_ms: 0,
             base_crit: 0.0,
             crit: 0.0,
             bonus_crit_percent: 0.0,
-            shield_amount: 0.0,
-            magic_shield_amount: 0.0,
-            physical_shield_amount: 0.0,
             mr_pen: 0.0,
             flat_mr_reduction: 0.0,
             percent_mr_reduction: 0.0,
@@ -188,9 +396,12 @@ mod tests {
             percent_bonus_armor_pen: 0.0,
             armor_reduction: 0.0,
             percent_armor_reduction: 0.0,
-            life_steal: 0,
-            spell_vamp: 0,
+            life_steal: 0.0,
+            omnivamp: 0.0,
+            physical_vamp: 0.0,
             tenacity: 0,
+            ability_haste: 0.0,
+            ultimate_haste: 0.0,
         };
 
         let stats_clone = stats.clone();
@@ -209,8 +420,7 @@ mod tests {
 
     #[test]
     fn test_calculate_crit_damage_multiplier_from_target() {
-        let champion = create_champion_by_name("test-bruiser");
-        let mut source = create_champion_by_name("test-bruiser");
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
 
         assert_eq!(calculate_crit_damage_multiplier_from_target(&source.champ_stats), 1.0);
 
@@ -225,13 +435,13 @@ mod tests {
 
     #[test]
     fn test_calculate_armor_reduction() {
-        let mut champion = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
 
         champion.champ_stats.armor = 100.0;
         champion.champ_stats.bonus_armor = 200.0;
 
-        let mut source = create_champion_by_name("test-bruiser");
-        let mut source_stats = &mut source.champ_stats;
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
+        let source_stats = &mut source.champ_stats;
 
         source_stats.lethality = 10.0;
         source_stats.percent_bonus_armor_pen = 0.45;
@@ -248,8 +458,8 @@ mod tests {
 
     #[test]
     fn test_calculate_magic_resist_reduction() {
-        let mut champion = create_champion_by_name("test-bruiser");
-        let mut source = create_champion_by_name("test-bruiser");
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
 
         champion.champ_stats.mr = 80.0;
 
@@ -264,4 +474,160 @@ mod tests {
 
         assert_eq!(champion.champ_stats.calculate_magic_resist_reduction(&source.champ_stats), -2.0);
     }
+
+    #[test]
+    fn test_add_tenacity() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+
+        champion.champ_stats.add_tenacity(30);
+        assert_eq!(champion.champ_stats.tenacity, 30);
+
+        champion.champ_stats.add_tenacity(20);
+        assert_eq!(champion.champ_stats.tenacity, 44);
+    }
+
+    #[test]
+    fn test_cooldown_multiplier() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        assert_eq!(champion.champ_stats.cooldown_multiplier(false), 1.0);
+
+        champion.champ_stats.ability_haste = 25.0;
+        champion.champ_stats.ultimate_haste = 75.0;
+
+        assert_eq!(champion.champ_stats.cooldown_multiplier(false), 0.8);
+        assert_eq!(champion.champ_stats.cooldown_multiplier(true), 0.5);
+    }
+
+    #[test]
+    fn test_mana_regenerates_up_to_max() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let stats = &mut champion.champ_stats;
+        stats.resource_type = ResourceType::Mana;
+        stats.base_resource = 300.0;
+        stats.base_rp5 = 15.0;
+        stats.calculate_stats_from_level(1);
+
+        assert!(stats.can_pay(300.0));
+        stats.pay(280.0);
+        assert!(!stats.can_pay(30.0));
+
+        // 15 mana per 5 seconds is 3 mana per second
+        for _ in 0..TICKS_PER_SECOND as i32 * 5 {
+            stats.regenerate_resource();
+        }
+
+        assert!((stats.resource - 35.0).abs() < 0.01);
+        assert!(stats.can_pay(30.0));
+
+        for _ in 0..TICKS_PER_SECOND as i32 * 600 {
+            stats.regenerate_resource();
+        }
+
+        assert_eq!(stats.resource, 300.0);
+    }
+
+    #[test]
+    fn test_fury_builds_on_hit() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let stats = &mut champion.champ_stats;
+        stats.resource_type = ResourceType::Fury;
+        stats.base_resource = 100.0;
+        stats.base_rp5 = 50.0;
+        stats.resource_per_hit = 40.0;
+        stats.calculate_stats_from_level(1);
+
+        assert_eq!(stats.resource, 0.0);
+
+        stats.regenerate_resource();
+        assert_eq!(stats.resource, 0.0);
+
+        for _ in 0..3 {
+            stats.gain_resource_on_hit();
+        }
+
+        assert_eq!(stats.resource, 100.0);
+    }
+
+    #[test]
+    fn test_health_and_no_resource_costs() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let stats = &mut champion.champ_stats;
+        stats.health = 100.0;
+
+        assert!(stats.can_pay(1000.0));
+
+        stats.resource_type = ResourceType::Health;
+        assert!(!stats.can_pay(100.0));
+
+        stats.pay(40.0);
+        assert_eq!(stats.health, 60.0);
+
+        // Health costs are rounded like any other health loss
+        stats.pay(10.4);
+        assert_eq!(stats.health, 50.0);
+    }
+
+    #[test]
+    fn test_hp5_with_percent_base_regen() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let stats = &mut champion.champ_stats;
+
+        stats.bonus_hp5 = 2.0;
+        stats.bonus_hp5_percent = 1.0;
+        stats.calculate_stats_from_level(1);
+
+        // Only the base regeneration is doubled
+        assert_eq!(stats.hp5, stats.base_hp5 * 2.0 + 2.0);
+    }
+
+    #[test]
+    fn test_max_and_current_health() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let stats = &mut champion.champ_stats;
+        stats.health = stats.max_health - 100.0;
+
+        assert_eq!(stats.missing_health(), 100.0);
+        assert_eq!(stats.health_percent(), (685.0 - 100.0) / 685.0);
+        assert_eq!(stats.overheal(150.0), 50.0);
+        assert_eq!(stats.overheal(50.0), 0.0);
+
+        stats.add_max_health(20.0);
+
+        assert_eq!(stats.max_health, 705.0);
+        assert_eq!(stats.missing_health(), 100.0);
+    }
+
+    #[test]
+    fn test_attack_speed_cap() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let stats = &mut champion.champ_stats;
+
+        stats.bonus_as = 10.0;
+        stats.calculate_as_from_level(1);
+        assert_eq!(stats.as_, 2.5);
+
+        stats.attack_speed_uncapped = true;
+        stats.calculate_as_from_level(1);
+        assert!(stats.as_ > 2.5);
+    }
+
+    #[test]
+    fn test_vamp_healing() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let stats = &mut champion.champ_stats;
+        let damage = Damage::new(100.0, 50.0, 10.0);
+
+        stats.life_steal = 0.1;
+        assert_eq!(stats.vamp_healing(damage, true, false), 16.0);
+        assert_eq!(stats.vamp_healing(damage, false, false), 0.0);
+
+        stats.life_steal = 0.0;
+        stats.physical_vamp = 0.25;
+        assert_eq!(stats.vamp_healing(damage, false, false), 25.0);
+
+        stats.physical_vamp = 0.0;
+        stats.omnivamp = 0.5;
+        assert_eq!(stats.vamp_healing(damage, false, false), 80.0);
+        assert_eq!(stats.vamp_healing(damage, false, true), 0.5 * AREA_VAMP_EFFECTIVENESS * 160.0);
+    }
 }
@@ -0,0 +1,700 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use crate::ability::{AbilitySlot, SkillOrder};
+use crate::constants::MAX_LEVEL;
+use crate::crit::CritModel;
+use crate::data;
+use crate::data::champions::ChampionRegistry;
+use crate::data::ddragon::import_data_dragon;
+use crate::data::overlay::Overlay;
+use crate::data::patch::GameData;
+use crate::data::validate::validate_data;
+use crate::rotation::{Accuracy, ExecutionProfile, ParseRotationError, Rotation};
+use crate::scenario::{BuildSetup, ScenarioSetup};
+use crate::simulation::{compare_execution, compare_patches, level_sweep, run_monte_carlo};
+
+const USAGE: &str = "Usage:
+    league-calculator                                  Run the example scenario
+    league-calculator list [--patch <patch>] [--items]
+                                                       List available champions, or items with --items
+    league-calculator import <champion.json> (--out <dir> | --patch <patch> | --force)
+                                                       Import a Data Dragon champion dump into another
+                                                       directory, a patch's data or, with --force, over
+                                                       the live data
+    league-calculator validate                         Check every data file and report all problems
+    league-calculator simulate <champion[:level]> <champion[:level]> [--patch <patch>] [--overlay <file>]...
+                             [--runes1 <file>] [--runes2 <file>] [--ranks1 <ranks>] [--ranks2 <ranks>]
+                             [--combo1 <combo> | --priority1 <rules>] [--combo2 <combo> | --priority2 <rules>]
+                             [--order1 <order>] [--order2 <order>] [--accuracy1 <accuracy>] [--accuracy2 <accuracy>]
+                             [--execution1 <execution>] [--execution2 <execution>] [--crits <model>] [--seed <n>]
+                             [--runs <n>]
+                                                       Run a scenario between two champions. Items can be
+                                                       added with +, e.g. 'aatrox:6+Long Sword+1042', and
+                                                       abilities learned with e.g. --ranks1 q3,w1,e2,r1 or
+                                                       levelled with a skill order, e.g. --order1 'Q>E>W'.
+                                                       A side can follow a combo, e.g. 'Q AA W E Q R AA', or
+                                                       a priority list, e.g. 'R if target < 40%, Q, AA'.
+                                                       Accuracy is 'average' (the default), 'perfect' or a
+                                                       multiplier of the average hit chance, and execution is
+                                                       'perfect' (the default) or 'realistic'. Crits are
+                                                       'random' (the default), 'pseudo-random' as in the game,
+                                                       or 'expected' for the average damage of every attack.
+                                                       With --runs, the fight is repeated and the spread of
+                                                       outcomes reported. Random decisions follow the seed that
+                                                       is printed, or the one given with --seed
+    league-calculator compare <patch> <patch> <champion[:level]> <champion[:level]> [--overlay <file>]...
+                             [--runes1 <file>] [--runes2 <file>] [--ranks1 <ranks>] [--ranks2 <ranks>]
+                             [--combo1 <combo> | --priority1 <rules>] [--combo2 <combo> | --priority2 <rules>]
+                             [--order1 <order>] [--order2 <order>] [--accuracy1 <accuracy>] [--accuracy2 <accuracy>]
+                             [--execution1 <execution>] [--execution2 <execution>] [--crits <model>] [--seed <n>]
+                                                       Run the same scenario under two patches. Overlays are
+                                                       applied to the second patch only
+    league-calculator sweep <champion> <champion[:level]> [--patch <patch>] [--overlay <file>]...
+                             [scenario options as for simulate, except --runs]
+                                                       Run the scenario with the first champion at every level
+                                                       from 1 to 18 against the same target
+    league-calculator execution <champion[:level]> <champion[:level]> [--patch <patch>] [--overlay <file>]...
+                             [scenario options as for simulate, except --execution1] [--runs <n>]
+                                                       Compare the first champion's DPS with perfect and with
+                                                       realistic execution, over 100 runs unless --runs is given";
+
+const DEFAULT_EXECUTION_RUNS: usize = 100;
+
+/// Options that are followed by a value. Any other argument starting with `--` is a flag.
+const VALUE_OPTIONS: &[&str] = &[
+    "--out", "--patch", "--overlay", "--runes1", "--runes2", "--ranks1", "--ranks2", "--order1", "--order2",
+    "--combo1", "--combo2", "--priority1", "--priority2", "--accuracy1", "--accuracy2", "--execution1",
+    "--execution2", "--crits", "--seed", "--runs",
+];
+
+pub fn run(args: &[String]) -> ExitCode {
+    match args[0].as_str() {
+        "list" => list(&args[1..]),
+        "import" => import(&args[1..]),
+        "validate" => validate(),
+        "simulate" => simulate(&args[1..]),
+        "compare" => compare(&args[1..]),
+        "sweep" => sweep(&args[1..]),
+        "execution" => execution(&args[1..]),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        command => {
+            eprintln!("Unknown command: {}", command);
+            eprintln!("{}", USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn list(args: &[String]) -> ExitCode {
+    match load_game_data(option_value(args, "--patch")) {
+        Ok(data) => {
+            let names = if has_flag(args, "--items") { data.items.names() } else { data.champions.names() };
+
+            for name in names {
+                println!("{}", name);
+            }
+
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn import(args: &[String]) -> ExitCode {
+    let source = match positional(args).first() {
+        Some(source) => PathBuf::from(source),
+        None => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let out_dir = match import_dir(args) {
+        Ok(dir) => dir,
+        Err(message) => {
+            eprintln!("Import failed: {}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Existing definitions are only used to carry over fields Data Dragon does not publish
+    let existing = ChampionRegistry::load(&out_dir).ok();
+
+    let report = match import_data_dragon(&source, existing.as_ref()) {
+        Ok(report) => report,
+        Err(error) => {
+            eprintln!("Import failed: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    for note in &report.notes {
+        println!("warning: {}", note);
+    }
+
+    if let Err(error) = fs::create_dir_all(&out_dir) {
+        eprintln!("Could not create {}: {}", out_dir.display(), error);
+        return ExitCode::FAILURE;
+    }
+
+    for champion in &report.champions {
+        let path = out_dir.join(format!("{}.json", champion.id));
+
+        if let Err(error) = write_json(&path, champion) {
+            eprintln!("Could not write {}: {}", path.display(), error);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    println!("Imported {} champions into {} ({} warnings)", report.champions.len(), out_dir.display(), report.notes.len());
+
+    ExitCode::SUCCESS
+}
+
+/// Where imported champions are written: `--out`, or the champions directory of `--patch`. The live
+/// data is only overwritten with `--force`.
+fn import_dir(args: &[String]) -> Result<PathBuf, String> {
+    match (option_value(args, "--out"), option_value(args, "--patch")) {
+        (Some(_), Some(_)) => Err(String::from("--out and --patch cannot be used together")),
+        (Some(dir), None) => Ok(PathBuf::from(dir)),
+        (None, patch) => {
+            let root = data::data_dir().map_err(|error| error.to_string())?;
+            let patch = patch.filter(|patch| *patch != data::patch::LIVE_PATCH);
+
+            if patch.is_none() && !has_flag(args, "--force") {
+                return Err(String::from("pass --out <dir> or --patch <patch>, or --force to overwrite the live data"));
+            }
+
+            data::patch::patch_dir(&root, patch)
+                .map(|dir| dir.join("champions"))
+                .map_err(|error| error.to_string())
+        }
+    }
+}
+
+fn validate() -> ExitCode {
+    let dir = match data::data_dir() {
+        Ok(dir) => dir,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let report = validate_data(&dir);
+
+    for problem in &report.problems {
+        eprintln!("{}", problem);
+    }
+
+    if report.is_valid() {
+        println!("Checked {} files in {}, no problems found", report.files, dir.display());
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("Checked {} files in {}, found {} problems", report.files, dir.display(), report.problems.len());
+        ExitCode::FAILURE
+    }
+}
+
+fn simulate(args: &[String]) -> ExitCode {
+    let champions = positional(args);
+
+    let setup = match scenario_setup(&champions) {
+        Some(setup) => setup,
+        None => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let setup = match with_scenario_options(setup, args) {
+        Ok(setup) => setup,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let runs = match option_value(args, "--runs").map(|runs| runs.parse::<usize>().ok().filter(|runs| *runs > 0)) {
+        Some(None) => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+        Some(runs) => runs,
+        None => None,
+    };
+
+    let result = load_game_data(option_value(args, "--patch"))
+        .and_then(|data| apply_overlays(data, &option_values(args, "--overlay")))
+        .and_then(|data| setup.resolve(&data))
+        .map(|mut scenario| match runs {
+            Some(runs) => println!("{}", run_monte_carlo(&mut scenario, runs)),
+            None => {
+                scenario.calculate_scenario();
+            }
+        });
+
+    match result {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn sweep(args: &[String]) -> ExitCode {
+    let champions = positional(args);
+
+    let setup = match scenario_setup(&champions) {
+        Some(setup) => setup,
+        None => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let setup = match with_scenario_options(setup, args) {
+        Ok(setup) => setup,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = load_game_data(option_value(args, "--patch"))
+        .and_then(|data| apply_overlays(data, &option_values(args, "--overlay")))
+        .and_then(|data| level_sweep(&setup, &data));
+
+    match result {
+        Ok(sweep) => {
+            println!("{}", sweep);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn execution(args: &[String]) -> ExitCode {
+    let champions = positional(args);
+
+    let setup = match scenario_setup(&champions) {
+        Some(setup) => setup,
+        None => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let setup = match with_scenario_options(setup, args) {
+        Ok(setup) => setup,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let runs = match option_value(args, "--runs").map(|runs| runs.parse::<usize>().ok().filter(|runs| *runs > 0)) {
+        Some(Some(runs)) => runs,
+        Some(None) => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+        None => DEFAULT_EXECUTION_RUNS,
+    };
+
+    let result = load_game_data(option_value(args, "--patch"))
+        .and_then(|data| apply_overlays(data, &option_values(args, "--overlay")))
+        .and_then(|data| compare_execution(&setup, &data, runs));
+
+    match result {
+        Ok(comparison) => {
+            println!("{}", comparison);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn compare(args: &[String]) -> ExitCode {
+    let values = positional(args);
+
+    if values.len() != 4 {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    }
+
+    let setup = match scenario_setup(&values[2..]) {
+        Some(setup) => setup,
+        None => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let setup = match with_scenario_options(setup, args) {
+        Ok(setup) => setup,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let comparison = load_patches(values[0], values[1], &option_values(args, "--overlay"))
+        .and_then(|(before, after)| compare_patches(&setup, &before, &after));
+
+    match comparison {
+        Ok(comparison) => {
+            println!();
+            println!("{}", comparison);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `live` refers to the data at the root of the data directory.
+fn load_game_data(patch: Option<&str>) -> Result<GameData, data::DataError> {
+    let patch = patch.filter(|patch| *patch != data::patch::LIVE_PATCH);
+
+    GameData::load(&data::data_dir()?, patch)
+}
+
+/// The data sets to compare. Overlays only apply to the second one, so comparing a patch with itself
+/// shows the effect of the overlays.
+fn load_patches(before: &str, after: &str, overlays: &[&str]) -> Result<(GameData, GameData), data::DataError> {
+    let before = load_game_data(Some(before))?;
+    let after = apply_overlays(load_game_data(Some(after))?, overlays)?;
+
+    Ok((before, after))
+}
+
+fn apply_overlays(mut data: GameData, paths: &[&str]) -> Result<GameData, data::DataError> {
+    for path in paths {
+        Overlay::load(Path::new(path))?.apply(&mut data)?;
+    }
+
+    Ok(data)
+}
+
+fn scenario_setup(champions: &[&str]) -> Option<ScenarioSetup> {
+    if champions.len() != 2 {
+        return None;
+    }
+
+    Some(ScenarioSetup::new(build_setup(champions[0])?, build_setup(champions[1])?))
+}
+
+/// Apply the options shared by every command that runs a scenario. Returns the message to print if
+/// any of them is invalid.
+fn with_scenario_options(setup: ScenarioSetup, args: &[String]) -> Result<ScenarioSetup, String> {
+    let setup = with_rune_pages(setup, args).map_err(|error| error.to_string())?;
+    let setup = with_ability_ranks(setup, args).ok_or(USAGE)?;
+    let setup = with_skill_orders(setup, args).ok_or(USAGE)?;
+    let setup = with_rotations(setup, args).map_err(|error| error.to_string())?;
+
+    let setup = with_accuracy(setup, args).ok_or(USAGE)?;
+    let setup = with_execution(setup, args).ok_or(USAGE)?;
+
+    let setup = with_crit_model(setup, args).ok_or(USAGE)?;
+
+    Ok(with_seed(setup, args).ok_or(USAGE)?)
+}
+
+/// Load the rune pages given with `--runes1` and `--runes2`. They are validated when the scenario is
+/// resolved against a data set.
+fn with_rune_pages(mut setup: ScenarioSetup, args: &[String]) -> Result<ScenarioSetup, data::DataError> {
+    if let Some(path) = option_value(args, "--runes1") {
+        setup.champ1 = setup.champ1.with_rune_page(data::read_json_file(Path::new(path))?);
+    }
+
+    if let Some(path) = option_value(args, "--runes2") {
+        setup.champ2 = setup.champ2.with_rune_page(data::read_json_file(Path::new(path))?);
+    }
+
+    Ok(setup)
+}
+
+/// Apply the ability ranks given with `--ranks1` and `--ranks2`. Returns `None` if either is invalid.
+fn with_ability_ranks(mut setup: ScenarioSetup, args: &[String]) -> Option<ScenarioSetup> {
+    if let Some(value) = option_value(args, "--ranks1") {
+        for (slot, rank) in ability_ranks(value)? {
+            setup.champ1 = setup.champ1.with_ability_rank(slot, rank);
+        }
+    }
+
+    if let Some(value) = option_value(args, "--ranks2") {
+        for (slot, rank) in ability_ranks(value)? {
+            setup.champ2 = setup.champ2.with_ability_rank(slot, rank);
+        }
+    }
+
+    Some(setup)
+}
+
+/// Apply the skill orders given with `--order1` and `--order2`. Returns `None` if either is invalid.
+fn with_skill_orders(mut setup: ScenarioSetup, args: &[String]) -> Option<ScenarioSetup> {
+    if let Some(value) = option_value(args, "--order1") {
+        setup.champ1 = setup.champ1.with_skill_order(SkillOrder::parse(value)?);
+    }
+
+    if let Some(value) = option_value(args, "--order2") {
+        setup.champ2 = setup.champ2.with_skill_order(SkillOrder::parse(value)?);
+    }
+
+    Some(setup)
+}
+
+/// Apply the rotations given with `--combo1`/`--priority1` and `--combo2`/`--priority2`. Sides without
+/// one keep the default rotation.
+fn with_rotations(mut setup: ScenarioSetup, args: &[String]) -> Result<ScenarioSetup, ParseRotationError> {
+    if let Some(rotation) = rotation(args, "--combo1", "--priority1")? {
+        setup.champ1_rotation = rotation;
+    }
+
+    if let Some(rotation) = rotation(args, "--combo2", "--priority2")? {
+        setup.champ2_rotation = rotation;
+    }
+
+    Ok(setup)
+}
+
+/// Apply the accuracy given with `--accuracy1` and `--accuracy2`. Returns `None` if either is invalid.
+fn with_accuracy(mut setup: ScenarioSetup, args: &[String]) -> Option<ScenarioSetup> {
+    if let Some(value) = option_value(args, "--accuracy1") {
+        setup.champ1_accuracy = Accuracy::parse(value)?;
+    }
+
+    if let Some(value) = option_value(args, "--accuracy2") {
+        setup.champ2_accuracy = Accuracy::parse(value)?;
+    }
+
+    Some(setup)
+}
+
+/// Apply the execution profiles given with `--execution1` and `--execution2`. Returns `None` if either
+/// is invalid.
+fn with_execution(mut setup: ScenarioSetup, args: &[String]) -> Option<ScenarioSetup> {
+    if let Some(value) = option_value(args, "--execution1") {
+        setup.champ1_execution = ExecutionProfile::parse(value)?;
+    }
+
+    if let Some(value) = option_value(args, "--execution2") {
+        setup.champ2_execution = ExecutionProfile::parse(value)?;
+    }
+
+    Some(setup)
+}
+
+/// Apply the crit model given with `--crits`. Returns `None` if it is invalid.
+fn with_crit_model(mut setup: ScenarioSetup, args: &[String]) -> Option<ScenarioSetup> {
+    if let Some(value) = option_value(args, "--crits") {
+        setup.crit_model = CritModel::parse(value)?;
+    }
+
+    Some(setup)
+}
+
+/// Apply the seed given with `--seed`. Returns `None` if it is not a number.
+fn with_seed(mut setup: ScenarioSetup, args: &[String]) -> Option<ScenarioSetup> {
+    if let Some(value) = option_value(args, "--seed") {
+        setup.seed = Some(value.parse::<u64>().ok()?);
+    }
+
+    Some(setup)
+}
+
+fn rotation(args: &[String], combo: &str, priority: &str) -> Result<Option<Rotation>, ParseRotationError> {
+    match (option_value(args, combo), option_value(args, priority)) {
+        (Some(combo), _) => Rotation::parse_combo(combo).map(Some),
+        (None, Some(priority)) => Rotation::parse_priority(priority).map(Some),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Parse comma separated ability ranks, e.g. `q3,w1,e2,r1`.
+fn ability_ranks(value: &str) -> Option<Vec<(AbilitySlot, usize)>> {
+    value.split(',')
+        .map(|part| {
+            let part = part.trim();
+            let slot = AbilitySlot::from_name(part.get(..1)?)?;
+            let rank = part.get(1..)?.parse::<usize>().ok()?;
+
+            Some((slot, rank))
+        })
+        .collect()
+}
+
+/// Parse `name` or `name:level`, optionally followed by `+item` for each item in the build.
+fn build_setup(value: &str) -> Option<BuildSetup> {
+    let mut parts = value.split('+');
+    let champion = parts.next()?;
+    let items: Vec<&str> = parts.collect();
+
+    if items.iter().any(|item| item.trim().is_empty()) {
+        return None;
+    }
+
+    let setup = match champion.split_once(':') {
+        Some((name, level)) => {
+            let level = level.parse::<i32>().ok().filter(|level| (1..=MAX_LEVEL).contains(level))?;
+            BuildSetup::new(name, level)
+        }
+        None => BuildSetup::new(champion, 1),
+    };
+
+    Some(setup.with_items(&items))
+}
+
+fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> std::io::Result<()> {
+    let mut contents = serde_json::to_string_pretty(value)?;
+    contents.push('\n');
+
+    fs::write(path, contents)
+}
+
+/// Arguments that are neither options nor option values.
+fn positional(args: &[String]) -> Vec<&str> {
+    let mut values = Vec::new();
+    let mut skip_next = false;
+
+    for arg in args {
+        if skip_next {
+            skip_next = false;
+        } else if arg.starts_with("--") {
+            skip_next = VALUE_OPTIONS.contains(&arg.as_str());
+        } else {
+            values.push(arg.as_str());
+        }
+    }
+
+    values
+}
+
+fn has_flag(args: &[String], name: &str) -> bool {
+    args.iter().any(|arg| arg == name)
+}
+
+fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    option_values(args, name).first().copied()
+}
+
+/// Every value given for an option that may be repeated.
+fn option_values<'a>(args: &'a [String], name: &str) -> Vec<&'a str> {
+    args.iter()
+        .zip(args.iter().skip(1))
+        .filter(|(arg, _)| *arg == name)
+        .map(|(_, value)| value.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ability::AbilitySlot;
+    use crate::cli::{ability_ranks, build_setup, import_dir, load_patches, option_value, option_values, positional};
+    use crate::data::data_dir;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_positional() {
+        let args = args(&["champion.json", "--out", "out", "extra"]);
+
+        assert_eq!(positional(&args), vec!["champion.json", "extra"]);
+    }
+
+    #[test]
+    fn test_positional_after_flag() {
+        let args = args(&["--items", "aatrox", "--patch", "14.3"]);
+
+        // Flags do not take the next argument
+        assert_eq!(positional(&args), vec!["aatrox"]);
+    }
+
+    #[test]
+    fn test_option_value() {
+        let args = args(&["champion.json", "--out", "out"]);
+
+        assert_eq!(option_value(&args, "--out"), Some("out"));
+        assert_eq!(option_value(&args, "--patch"), None);
+    }
+
+    #[test]
+    fn test_option_values() {
+        let args = args(&["--overlay", "a.json", "champion", "--overlay", "b.json"]);
+
+        assert_eq!(option_values(&args, "--overlay"), vec!["a.json", "b.json"]);
+        assert!(option_values(&args, "--patch").is_empty());
+    }
+
+    #[test]
+    fn test_build_setup() {
+        let setup = build_setup("aatrox:6").unwrap();
+
+        assert_eq!(setup.champion, "aatrox");
+        assert_eq!(setup.level, 6);
+        assert_eq!(build_setup("aatrox").unwrap().level, 1);
+        assert!(build_setup("aatrox:19").is_none());
+        assert!(build_setup("aatrox:six").is_none());
+    }
+
+    #[test]
+    fn test_build_setup_with_items() {
+        let setup = build_setup("aatrox:6+Long Sword+1042").unwrap();
+
+        assert_eq!(setup.champion, "aatrox");
+        assert_eq!(setup.level, 6);
+        assert_eq!(setup.items, vec!["Long Sword", "1042"]);
+        assert!(build_setup("aatrox").unwrap().items.is_empty());
+        assert!(build_setup("aatrox+").is_none());
+    }
+
+    #[test]
+    fn test_ability_ranks() {
+        assert_eq!(ability_ranks("q3, W1,r1").unwrap(), vec![(AbilitySlot::Q, 3), (AbilitySlot::W, 1), (AbilitySlot::R, 1)]);
+        assert!(ability_ranks("x3").is_none());
+        assert!(ability_ranks("q").is_none());
+        assert!(ability_ranks("").is_none());
+    }
+
+    #[test]
+    fn test_compare_applies_overlays_to_second_patch() {
+        let overlay = data_dir().unwrap().join("overlays").join("aatrox-ad-buff.json");
+        let (before, after) = load_patches("live", "live", &[overlay.to_str().unwrap()]).unwrap();
+
+        assert!(before.overlays.is_empty());
+        assert_eq!(after.overlays.len(), 1);
+        assert!(after.create_champion("aatrox").unwrap().champ_stats.ad > before.create_champion("aatrox").unwrap().champ_stats.ad);
+    }
+
+    #[test]
+    fn test_import_dir() {
+        let root = data_dir().unwrap();
+
+        assert!(import_dir(&args(&["champion.json"])).is_err());
+        assert!(import_dir(&args(&["champion.json", "--patch", "live"])).is_err());
+        assert_eq!(import_dir(&args(&["champion.json", "--force"])).unwrap(), root.join("champions"));
+        assert_eq!(import_dir(&args(&["champion.json", "--patch", "live", "--force"])).unwrap(), root.join("champions"));
+        assert_eq!(import_dir(&args(&["champion.json", "--patch", "14.3"])).unwrap(), root.join("patches").join("14.3").join("champions"));
+        assert_eq!(import_dir(&args(&["champion.json", "--out", "out"])).unwrap().to_str(), Some("out"));
+        assert!(import_dir(&args(&["champion.json", "--out", "out", "--patch", "14.3"])).is_err());
+        assert!(import_dir(&args(&["champion.json", "--patch", "../.."])).is_err());
+    }
+}
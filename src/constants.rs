@@ -1,2 +1,10 @@
 pub(crate) const TICKS_PER_SECOND: f32 = 30.0;
 pub(crate) const TICK_SECOND: f32 = 1.0 / TICKS_PER_SECOND;
+pub(crate) const DATA_DIR_NAME: &str = "data";
+pub(crate) const DATA_DIR_ENV_VAR: &str = "LEAGUE_DATA_DIR";
+pub(crate) const HEALTH_REGEN_INTERVAL: f32 = 0.5; // In seconds; health regenerates in steps like in the game
+pub(crate) const AREA_VAMP_EFFECTIVENESS: f32 = 1.0 / 3.0; // Omnivamp from area damage and pets
+pub(crate) const ATTACK_SPEED_CAP: f32 = 2.5;
+pub(crate) const DEFAULT_REACTION_DELAY: f32 = 0.5; // In seconds; how long the side that is engaged on takes to respond
+pub(crate) const MAX_LEVEL: i32 = 18;
+pub(crate) const MAX_FIGHT_DURATION: f32 = 600.0; // In seconds; stops fights where neither side can win
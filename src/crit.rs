@@ -0,0 +1,178 @@
+use std::fmt;
+use rand::Rng;
+
+/// How auto attacks decide whether they critically strike.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CritModel {
+    /// Every attack rolls the crit chance on its own.
+    #[default]
+    Random,
+    /// The game's pseudo-random distribution: the chance starts low and goes up with every attack that
+    /// does not crit, so crits come at steadier intervals with the same average rate.
+    PseudoRandom,
+    /// Every attack deals its average damage, for comparisons without randomness.
+    Expected,
+}
+
+/// A champion's crit model and what it remembers between attacks.
+#[derive(Debug, Clone, Default)]
+pub struct CritState {
+    pub(crate) model: CritModel,
+    pub(crate) attacks_without_crit: u32,
+    pub(crate) constant: Option<(f32, f32)>, // (crit chance, pseudo-random constant) for the last chance used
+}
+
+impl CritModel {
+    /// Parse `random`, `pseudo-random` (or `prd`) or `expected`.
+    pub fn parse(text: &str) -> Option<CritModel> {
+        match text.trim().to_lowercase().as_str() {
+            "random" => Some(CritModel::Random),
+            "pseudo-random" | "prd" => Some(CritModel::PseudoRandom),
+            "expected" => Some(CritModel::Expected),
+            _ => None,
+        }
+    }
+}
+
+impl CritState {
+    pub fn new(model: CritModel) -> CritState {
+        CritState {
+            model,
+            ..CritState::default()
+        }
+    }
+
+    /// The multiplier for the damage of an attack, given the crit chance and the multiplier of a crit.
+    pub fn damage_multiplier(&mut self, chance: f32, crit_multiplier: f32, rng: &mut impl Rng) -> f32 {
+        if chance <= 0.0 {
+            return 1.0;
+        }
+
+        let crits = match self.model {
+            CritModel::Random => rng.gen::<f32>() <= chance,
+            CritModel::PseudoRandom => {
+                let constant = self.constant(chance);
+                self.attacks_without_crit += 1;
+
+                let crits = rng.gen::<f32>() < constant * self.attacks_without_crit as f32;
+
+                if crits {
+                    self.attacks_without_crit = 0;
+                }
+
+                crits
+            }
+            CritModel::Expected => return 1.0 + chance.min(1.0) * (crit_multiplier - 1.0),
+        };
+
+        if crits { crit_multiplier } else { 1.0 }
+    }
+
+    fn constant(&mut self, chance: f32) -> f32 {
+        match self.constant {
+            Some((cached_chance, constant)) if cached_chance == chance => constant,
+            _ => {
+                let constant = pseudo_random_constant(chance);
+                self.constant = Some((chance, constant));
+
+                constant
+            }
+        }
+    }
+}
+
+/// How much the chance to crit goes up with each attack that does not crit, for a pseudo-random
+/// distribution that crits `chance` of the time on average.
+pub fn pseudo_random_constant(chance: f32) -> f32 {
+    if chance >= 1.0 {
+        return 1.0;
+    }
+
+    let (mut low, mut high) = (0.0, chance as f64);
+
+    for _ in 0..40 {
+        let middle = (low + high) / 2.0;
+
+        if average_crit_rate(middle) < chance as f64 {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    high as f32
+}
+
+/// The fraction of attacks that crit when the chance goes up by `constant` with each attack.
+fn average_crit_rate(constant: f64) -> f64 {
+    let mut expected_attacks = 0.0;
+    let mut no_crit_yet = 1.0;
+    let mut attacks = 1.0;
+
+    while no_crit_yet > 1e-9 {
+        let chance = (attacks * constant).min(1.0);
+
+        expected_attacks += attacks * no_crit_yet * chance;
+        no_crit_yet *= 1.0 - chance;
+        attacks += 1.0;
+    }
+
+    1.0 / expected_attacks
+}
+
+impl fmt::Display for CritModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CritModel::Random => write!(f, "random"),
+            CritModel::PseudoRandom => write!(f, "pseudo-random"),
+            CritModel::Expected => write!(f, "expected"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::crit::{pseudo_random_constant, CritModel, CritState};
+
+    #[test]
+    fn test_pseudo_random_constant() {
+        assert!((pseudo_random_constant(0.25) - 0.08474).abs() < 0.0001);
+        assert!((pseudo_random_constant(0.5) - 0.30210).abs() < 0.0001);
+        assert_eq!(pseudo_random_constant(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_pseudo_random_crits() {
+        let mut state = CritState::new(CritModel::PseudoRandom);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut crits = 0;
+        let mut longest_streak = 0;
+        let mut streak = 0;
+
+        for _ in 0..10000 {
+            if state.damage_multiplier(0.25, 1.75, &mut rng) > 1.0 {
+                crits += 1;
+                streak = 0;
+            } else {
+                streak += 1;
+                longest_streak = longest_streak.max(streak);
+            }
+        }
+
+        // The average rate is kept, and the chance reaches 100% after 12 attacks without a crit
+        assert!((crits as f32 / 10000.0 - 0.25).abs() < 0.02, "{} crits", crits);
+        assert!(longest_streak < 12);
+    }
+
+    #[test]
+    fn test_expected_crits() {
+        let mut state = CritState::new(CritModel::Expected);
+
+        assert_eq!(state.damage_multiplier(0.5, 1.75, &mut rand::thread_rng()), 1.375);
+        assert_eq!(state.damage_multiplier(0.0, 1.75, &mut rand::thread_rng()), 1.0);
+        assert_eq!(CritModel::parse("PRD"), Some(CritModel::PseudoRandom));
+        assert_eq!(CritModel::parse("sometimes"), None);
+    }
+}
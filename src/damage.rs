@@ -1,6 +1,7 @@
-use std::ops::{Add, AddAssign};
+use std::ops::{Add, AddAssign, Mul};
+use crate::effects::DamageType;
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
 pub struct Damage {
     pub(crate) physical_component: f32,
     pub(crate) magical_component: f32,
@@ -27,6 +28,18 @@ impl Add for Damage {
     }
 }
 
+impl Mul<f32> for Damage {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self {
+        Damage {
+            physical_component: self.physical_component * rhs,
+            magical_component: self.magical_component * rhs,
+            true_component: self.true_component * rhs,
+        }
+    }
+}
+
 impl Damage {
     pub fn new(physical_component: f32, magical_component: f32, true_component: f32) -> Self {
         Damage {
@@ -36,6 +49,15 @@ impl Damage {
         }
     }
 
+    /// Damage of a single type.
+    pub fn from_type(damage_type: &DamageType, amount: f32) -> Self {
+        match damage_type {
+            DamageType::Physical => Damage::new(amount, 0.0, 0.0),
+            DamageType::Magical => Damage::new(0.0, amount, 0.0),
+            DamageType::True => Damage::new(0.0, 0.0, amount),
+        }
+    }
+
     pub fn total(&self) -> f32 {
         self.physical_component + self.magical_component + self.true_component
     }
@@ -56,6 +78,7 @@ impl Damage {
 #[cfg(test)]
 mod tests {
     use crate::damage::Damage;
+    use crate::effects::DamageType;
 
     #[test]
     fn test_damage() {
@@ -75,6 +98,14 @@ mod tests {
         assert_eq!(damage.true_component, 50.0);
     }
 
+    #[test]
+    fn test_from_type() {
+        let damage = Damage::from_type(&DamageType::Magical, 100.0);
+
+        assert_eq!(damage.magical_component, 100.0);
+        assert_eq!(damage.physical_component + damage.true_component, 0.0);
+    }
+
     #[test]
     fn test_add_assign() {
         let mut damage1 = Damage::new(100.0, 100.0, 100.0);
@@ -102,6 +133,17 @@ mod tests {
     }
 
     #[test]
+    fn test_mul() {
+        let damage = Damage::new(100.0, 50.0, 10.0) * 1.5;
+
+        assert_eq!(damage.total(), 240.0);
+        assert_eq!(damage.physical_component, 150.0);
+        assert_eq!(damage.magical_component, 75.0);
+        assert_eq!(damage.true_component, 15.0);
+    }
+
+    #[test]
+    #[allow(clippy::clone_on_copy)]
     fn test_clone() {
         let damage1 = Damage::new(100.0, 100.0, 100.0);
         let mut damage2 = damage1.clone();
@@ -0,0 +1,180 @@
+pub(crate) mod champions;
+pub(crate) mod ddragon;
+pub(crate) mod items;
+pub(crate) mod overlay;
+pub(crate) mod patch;
+pub(crate) mod runes;
+pub(crate) mod validate;
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use serde::de::DeserializeOwned;
+use crate::constants::{DATA_DIR_ENV_VAR, DATA_DIR_NAME};
+use crate::data::champions::ChampionRegistry;
+
+static CHAMPION_REGISTRY: OnceLock<ChampionRegistry> = OnceLock::new();
+
+#[derive(Debug)]
+pub enum DataError {
+    Io { path: PathBuf, source: std::io::Error },
+    Parse { path: PathBuf, source: serde_json::Error },
+    Duplicate { id: String, path: PathBuf },
+    UnknownPatch { patch: String, available: Vec<String> },
+    InvalidPatch { patch: String },
+    UnknownChampion { name: String, suggestions: Vec<String> },
+    UnknownItem { name: String, suggestions: Vec<String> },
+    UnknownRune { name: String, suggestions: Vec<String> },
+    RunePage { page: String, message: String },
+    Overlay { overlay: String, message: String },
+    MissingDataDir { searched: Vec<PathBuf> },
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            DataError::Parse { path, source } => write!(f, "{}: {}", path.display(), source),
+            DataError::Duplicate { id, path } => write!(f, "{}: duplicate id '{}'", path.display(), id),
+            DataError::UnknownPatch { patch, available } => {
+                write!(f, "Unknown patch '{}' (available: {})", patch, available.join(", "))
+            }
+            DataError::InvalidPatch { patch } => {
+                write!(f, "Invalid patch '{}': must be a version such as 14.3, not a path", patch)
+            }
+            DataError::UnknownChampion { name, suggestions } => {
+                write!(f, "Champion not found: {}", name)?;
+                write_suggestions(f, suggestions)
+            }
+            DataError::UnknownItem { name, suggestions } => {
+                write!(f, "Item not found: {}", name)?;
+                write_suggestions(f, suggestions)
+            }
+            DataError::UnknownRune { name, suggestions } => {
+                write!(f, "Rune not found: {}", name)?;
+                write_suggestions(f, suggestions)
+            }
+            DataError::RunePage { page, message } => write!(f, "Rune page '{}': {}", page, message),
+            DataError::Overlay { overlay, message } => write!(f, "Overlay '{}': {}", overlay, message),
+            DataError::MissingDataDir { searched } => {
+                let searched: Vec<String> = searched.iter().map(|dir| dir.display().to_string()).collect();
+                write!(f, "Game data not found (looked in {}); set {} to the data directory", searched.join(", "), DATA_DIR_ENV_VAR)
+            }
+        }
+    }
+}
+
+fn write_suggestions(f: &mut fmt::Formatter<'_>, suggestions: &[String]) -> fmt::Result {
+    if !suggestions.is_empty() {
+        write!(f, " (did you mean {}?)", suggestions.join(", "))?;
+    }
+
+    Ok(())
+}
+
+impl std::error::Error for DataError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DataError::Io { source, .. } => Some(source),
+            DataError::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// The root of the game data directory: `LEAGUE_DATA_DIR` if it is set, otherwise a `data`
+/// directory in the working directory, or next to the executable or in one of its parent
+/// directories, which finds the repository's data from `target/`.
+pub fn data_dir() -> Result<PathBuf, DataError> {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV_VAR) {
+        return find_data_dir(vec![PathBuf::from(dir)]);
+    }
+
+    let mut candidates = Vec::new();
+
+    if let Ok(dir) = std::env::current_dir() {
+        candidates.push(dir.join(DATA_DIR_NAME));
+    }
+
+    if let Ok(exe) = std::env::current_exe() {
+        candidates.extend(exe.ancestors().skip(1).map(|dir| dir.join(DATA_DIR_NAME)));
+    }
+
+    find_data_dir(candidates)
+}
+
+/// The first of `candidates` that holds champion data.
+fn find_data_dir(candidates: Vec<PathBuf>) -> Result<PathBuf, DataError> {
+    match candidates.iter().position(|dir| dir.join("champions").is_dir()) {
+        Some(index) => Ok(candidates[index].clone()),
+        None => Err(DataError::MissingDataDir { searched: candidates }),
+    }
+}
+
+/// The champion registry loaded from the default data directory. Loaded once on first successful use.
+pub fn champion_registry() -> Result<&'static ChampionRegistry, DataError> {
+    if let Some(registry) = CHAMPION_REGISTRY.get() {
+        return Ok(registry);
+    }
+
+    let registry = ChampionRegistry::load(&data_dir()?.join("champions"))?;
+
+    Ok(CHAMPION_REGISTRY.get_or_init(|| registry))
+}
+
+/// Parse every `.json` file in a directory, sorted by file name so that load order is stable.
+pub(crate) fn read_json_dir<T: DeserializeOwned>(dir: &Path) -> Result<Vec<(PathBuf, T)>, DataError> {
+    let mut parsed = Vec::new();
+
+    for path in json_files(dir)? {
+        let value = read_json_file(&path)?;
+        parsed.push((path, value));
+    }
+
+    Ok(parsed)
+}
+
+/// Every `.json` file in a directory, sorted by file name.
+pub(crate) fn json_files(dir: &Path) -> Result<Vec<PathBuf>, DataError> {
+    let entries = fs::read_dir(dir).map_err(|source| DataError::Io { path: dir.to_path_buf(), source })?;
+
+    let mut paths = Vec::new();
+
+    for entry in entries {
+        let entry = entry.map_err(|source| DataError::Io { path: dir.to_path_buf(), source })?;
+        let path = entry.path();
+
+        if path.extension().is_some_and(|extension| extension == "json") {
+            paths.push(path);
+        }
+    }
+
+    paths.sort();
+
+    Ok(paths)
+}
+
+pub(crate) fn read_json_file<T: DeserializeOwned>(path: &Path) -> Result<T, DataError> {
+    let contents = fs::read_to_string(path).map_err(|source| DataError::Io { path: path.to_path_buf(), source })?;
+
+    serde_json::from_str(&contents).map_err(|source| DataError::Parse { path: path.to_path_buf(), source })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::{data_dir, find_data_dir, DataError};
+
+    #[test]
+    fn test_find_data_dir() {
+        let missing = std::env::temp_dir().join(format!("league-calculator-no-data-{}", std::process::id()));
+        let found = find_data_dir(vec![missing.clone(), data_dir().unwrap()]).unwrap();
+
+        assert_eq!(found, data_dir().unwrap());
+
+        match find_data_dir(vec![missing.clone()]) {
+            Err(DataError::MissingDataDir { searched }) => assert_eq!(searched, vec![missing]),
+            other => panic!("Expected a missing data directory, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::ability::{Ability, AbilityState, SkillOrder};
+use crate::champion::Champion;
+use crate::champion::stats::{ChampStats, ResourceType};
+use crate::crit::CritState;
+use crate::data::{read_json_dir, DataError};
+use crate::shield::ShieldStack;
+use crate::utils::{closest_matches, normalize_name};
+
+pub(crate) const MAX_SUGGESTIONS: usize = 3;
+
+/// The on-disk definition of a champion. Only base values and growths are stored; everything else
+/// on `ChampStats` is derived from these when the champion is created or levelled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChampionData {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) base_health: f32,
+    pub(crate) base_health_growth: f32,
+    pub(crate) base_hp5: f32,
+    pub(crate) base_hp5_growth: f32,
+    pub(crate) base_resource: f32,
+    pub(crate) base_resource_growth: f32,
+    pub(crate) base_rp5: f32,
+    pub(crate) base_rp5_growth: f32,
+    #[serde(default)]
+    pub(crate) resource_type: ResourceType,
+    #[serde(default)]
+    pub(crate) resource_per_hit: f32, // Only used by fury
+    pub(crate) base_ad: f32,
+    pub(crate) base_ad_growth: f32,
+    pub(crate) base_as: f32,
+    pub(crate) base_as_growth_percent: f32,
+    pub(crate) attack_windup: f32,
+    pub(crate) as_ratio: f32,
+    pub(crate) base_armor: f32,
+    pub(crate) base_armor_growth: f32,
+    pub(crate) base_mr: f32,
+    pub(crate) base_mr_growth: f32,
+    pub(crate) base_range: i32,
+    pub(crate) base_ms: i32,
+    pub(crate) base_crit: f32,
+    // Training dummies never attack, so the attack stats are allowed to be zero
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) training_dummy: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) abilities: Vec<Ability>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) skill_order: Option<SkillOrder>,
+}
+
+impl ChampionData {
+    /// Create a level 1 champion from this definition.
+    pub fn to_champion(&self) -> Champion {
+        Champion {
+            name: self.name.clone(),
+            level: 1,
+            friendly_limited_use_on_hit_effects: BTreeMap::new(),
+            friendly_duration_on_hit_effects: BTreeMap::new(),
+            friendly_stacking_on_hit_effects: BTreeMap::new(),
+            enemy_dot_on_hit_effects: BTreeMap::new(),
+            enemy_stacking_on_hit_effects: BTreeMap::new(),
+            keystone: None,
+            abilities: self.abilities.iter()
+                .map(|ability| (ability.slot, AbilityState::new(ability.clone())))
+                .collect(),
+            skill_order: self.skill_order.clone(),
+            crowd_control: HashMap::new(),
+            healing_done: 0.0,
+            shields: ShieldStack::default(),
+            crit_state: CritState::default(),
+            champ_stats: ChampStats {
+                base_health: self.base_health,
+                base_health_growth: self.base_health_growth,
+                max_health: self.base_health,
+                health: self.base_health,
+                bonus_health: 0.0,
+                base_hp5: self.base_hp5,
+                base_hp5_growth: self.base_hp5_growth,
+                hp5: self.base_hp5,
+                bonus_hp5: 0.0,
+                bonus_hp5_percent: 0.0,
+                base_resource: self.base_resource,
+                base_resource_growth: self.base_resource_growth,
+                resource_type: self.resource_type,
+                max_resource: self.base_resource.round(),
+                resource: self.base_resource.round(),
+                bonus_resource: 0.0,
+                base_rp5: self.base_rp5,
+                base_rp5_growth: self.base_rp5_growth,
+                rp5: self.base_rp5,
+                bonus_rp5: 0.0,
+                resource_per_hit: self.resource_per_hit,
+                base_ad: self.base_ad,
+                base_ad_growth: self.base_ad_growth,
+                ad: self.base_ad.round() as i32,
+                bonus_ad: 0.0,
+                base_as: self.base_as,
+                base_as_growth_percent: self.base_as_growth_percent,
+                attack_windup: self.attack_windup,
+                as_: self.base_as,
+                attack_speed_uncapped: false,
+                as_ratio: self.as_ratio,
+                bonus_as: 0.0,
+                ap: 0.0,
+                base_armor: self.base_armor,
+                base_armor_growth: self.base_armor_growth,
+                armor: self.base_armor,
+                bonus_armor: 0.0,
+                base_mr: self.base_mr,
+                base_mr_growth: self.base_mr_growth,
+                mr: self.base_mr,
+                bonus_mr: 0.0,
+                base_range: self.base_range,
+                range: self.base_range,
+                bonus_range: 0,
+                base_ms: self.base_ms,
+                ms: self.base_ms,
+                bonus_ms: 0,
+                base_crit: self.base_crit,
+                crit: self.base_crit,
+                bonus_crit_percent: 0.0,
+                mr_pen: 0.0,
+                flat_mr_reduction: 0.0,
+                percent_mr_reduction: 0.0,
+                percent_mr_pen: 0.0,
+                lethality: 0.0,
+                percent_bonus_armor_pen: 0.0,
+                armor_reduction: 0.0,
+                percent_armor_reduction: 0.0,
+                life_steal: 0.0,
+                omnivamp: 0.0,
+                physical_vamp: 0.0,
+                tenacity: 0,
+                ability_haste: 0.0,
+                ultimate_haste: 0.0,
+            },
+        }
+    }
+}
+
+/// All known champion definitions. Lookups ignore case and punctuation, and accept either the
+/// champion's id or display name, so "Kai'Sa", "kaisa" and "KaiSa" all find the same champion.
+#[derive(Debug, Clone, Default)]
+pub struct ChampionRegistry {
+    champions: HashMap<String, ChampionData>,
+    aliases: HashMap<String, String>,
+}
+
+impl ChampionRegistry {
+    /// Load every champion file (one champion per `.json` file) in a directory.
+    pub fn load(dir: &Path) -> Result<ChampionRegistry, DataError> {
+        let mut registry = ChampionRegistry::default();
+
+        for (path, data) in read_json_dir::<ChampionData>(dir)? {
+            if registry.champions.contains_key(&normalize_name(&data.id)) {
+                return Err(DataError::Duplicate { id: data.id, path });
+            }
+
+            registry.insert(data);
+        }
+
+        Ok(registry)
+    }
+
+    pub fn insert(&mut self, data: ChampionData) {
+        let key = normalize_name(&data.id);
+
+        self.aliases.insert(normalize_name(&data.name), key.clone());
+        self.champions.insert(key, data);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ChampionData> {
+        let key = normalize_name(name);
+
+        match self.champions.get(&key) {
+            Some(data) => Some(data),
+            None => self.aliases.get(&key).and_then(|id| self.champions.get(id)),
+        }
+    }
+
+    /// Look up a champion, suggesting similarly named champions when it cannot be found.
+    pub fn find(&self, name: &str) -> Result<&ChampionData, DataError> {
+        match self.get(name) {
+            Some(data) => Ok(data),
+            None => Err(DataError::UnknownChampion {
+                name: name.to_string(),
+                suggestions: self.suggestions(name),
+            }),
+        }
+    }
+
+    pub fn create_champion(&self, name: &str) -> Result<Champion, DataError> {
+        self.find(name).map(|data| data.to_champion())
+    }
+
+    /// Display names of every champion, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.champions.values().map(|data| data.name.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    /// Up to `MAX_SUGGESTIONS` champion names closest to `name` by edit distance, closest first.
+    fn suggestions(&self, name: &str) -> Vec<String> {
+        let candidates = self.champions.iter()
+            .map(|(key, data)| (vec![key.clone(), normalize_name(&data.name)], data.name.as_str()));
+
+        closest_matches(name, candidates, MAX_SUGGESTIONS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::champions::{ChampionData, ChampionRegistry};
+    use crate::data::{data_dir, DataError};
+
+    const TEST_CHAMPION: &str = r#"{
+        "id": "test",
+        "name": "Test",
+        "base_health": 600.0,
+        "base_health_growth": 100.0,
+        "base_hp5": 8.5,
+        "base_hp5_growth": 0.5,
+        "base_resource": 300.0,
+        "base_resource_growth": 40.0,
+        "base_rp5": 7.5,
+        "base_rp5_growth": 0.7,
+        "base_ad": 55.0,
+        "base_ad_growth": 3.0,
+        "base_as": 0.625,
+        "base_as_growth_percent": 0.02,
+        "attack_windup": 0.2,
+        "as_ratio": 0.625,
+        "base_armor": 30.0,
+        "base_armor_growth": 4.0,
+        "base_mr": 30.0,
+        "base_mr_growth": 1.3,
+        "base_range": 550,
+        "base_ms": 330,
+        "base_crit": 0.0
+    }"#;
+
+    #[test]
+    fn test_parse_champion_data() {
+        let data: ChampionData = serde_json::from_str(TEST_CHAMPION).unwrap();
+
+        assert_eq!(data.id, "test");
+        assert_eq!(data.name, "Test");
+        assert_eq!(data.base_health_growth, 100.0);
+        assert_eq!(data.base_range, 550);
+    }
+
+    #[test]
+    fn test_parse_champion_data_rejects_unknown_fields() {
+        let contents = TEST_CHAMPION.replace("\"base_crit\"", "\"base_critical\"");
+
+        assert!(serde_json::from_str::<ChampionData>(&contents).is_err());
+    }
+
+    #[test]
+    fn test_to_champion() {
+        let data: ChampionData = serde_json::from_str(TEST_CHAMPION).unwrap();
+        let champion = data.to_champion();
+
+        assert_eq!(champion.name, "Test");
+        assert_eq!(champion.level, 1);
+        assert_eq!(champion.champ_stats.health, 600.0);
+        assert_eq!(champion.champ_stats.hp5, 8.5);
+        assert_eq!(champion.champ_stats.resource, 300.0);
+        assert_eq!(champion.champ_stats.ad, 55);
+        assert_eq!(champion.champ_stats.as_, 0.625);
+        assert_eq!(champion.champ_stats.armor, 30.0);
+        assert_eq!(champion.champ_stats.mr, 30.0);
+        assert_eq!(champion.champ_stats.range, 550);
+    }
+
+    #[test]
+    fn test_registry_lookup_is_case_insensitive() {
+        let mut registry = ChampionRegistry::default();
+        registry.insert(serde_json::from_str(TEST_CHAMPION).unwrap());
+
+        assert!(registry.get("TEST").is_some());
+        assert!(registry.create_champion("Test").is_ok());
+        assert!(registry.get("missing").is_none());
+    }
+
+    #[test]
+    fn test_registry_lookup_ignores_punctuation() {
+        let mut registry = ChampionRegistry::default();
+        let mut data: ChampionData = serde_json::from_str(TEST_CHAMPION).unwrap();
+        data.id = String::from("KSante");
+        data.name = String::from("K'Sante");
+        registry.insert(data);
+
+        assert!(registry.get("K'Sante").is_some());
+        assert!(registry.get("ksante").is_some());
+        assert!(registry.get("KSante").is_some());
+        assert!(registry.get("k sante").is_some());
+    }
+
+    #[test]
+    fn test_registry_lookup_by_display_name() {
+        let registry = ChampionRegistry::load(&data_dir().unwrap().join("champions")).unwrap();
+
+        assert_eq!(registry.get("Test Bruiser").unwrap().id, "test-bruiser");
+        assert_eq!(registry.get("testbruiser").unwrap().id, "test-bruiser");
+    }
+
+    #[test]
+    fn test_registry_find_suggests_similar_names() {
+        let registry = ChampionRegistry::load(&data_dir().unwrap().join("champions")).unwrap();
+
+        match registry.find("atrox") {
+            Err(DataError::UnknownChampion { name, suggestions }) => {
+                assert_eq!(name, "atrox");
+                assert_eq!(suggestions, vec![String::from("Aatrox")]);
+            }
+            _ => panic!("Expected an unknown champion error"),
+        }
+
+        match registry.find("zzzzzzzz") {
+            Err(DataError::UnknownChampion { suggestions, .. }) => assert!(suggestions.is_empty()),
+            _ => panic!("Expected an unknown champion error"),
+        }
+    }
+
+    #[test]
+    fn test_registry_names() {
+        let registry = ChampionRegistry::load(&data_dir().unwrap().join("champions")).unwrap();
+
+        assert_eq!(registry.names(), vec!["Aatrox", "Dummy", "Test Bruiser"]);
+    }
+
+    #[test]
+    fn test_load_registry_from_data_dir() {
+        let registry = ChampionRegistry::load(&data_dir().unwrap().join("champions")).unwrap();
+
+        assert_eq!(registry.get("aatrox").unwrap().name, "Aatrox");
+        assert_eq!(registry.get("test-bruiser").unwrap().base_health, 685.0);
+        assert_eq!(registry.get("dummy").unwrap().base_health, 10000.0);
+    }
+}
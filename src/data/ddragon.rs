@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use serde::Deserialize;
+use crate::champion::stats::ResourceType;
+use crate::data::champions::{ChampionData, ChampionRegistry};
+use crate::data::{read_json_file, DataError};
+
+/// Data Dragon does not publish attack windups; this is used when no existing definition has one.
+const DEFAULT_ATTACK_WINDUP: f32 = 0.25;
+
+/// Data Dragon stat keys that have a matching `ChampionData` field.
+const MAPPED_STATS: [&str; 19] = [
+    "hp", "hpperlevel", "hpregen", "hpregenperlevel", "mp", "mpperlevel", "mpregen", "mpregenperlevel",
+    "attackdamage", "attackdamageperlevel", "attackspeed", "attackspeedperlevel", "armor", "armorperlevel",
+    "spellblock", "spellblockperlevel", "attackrange", "movespeed", "crit",
+];
+
+#[derive(Debug, Deserialize)]
+struct DataDragonFile {
+    data: BTreeMap<String, DataDragonChampion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataDragonChampion {
+    id: String,
+    name: String,
+    #[serde(default)]
+    partype: String,
+    stats: BTreeMap<String, f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportNote {
+    pub(crate) champion: String,
+    pub(crate) field: String,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for ImportNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.champion, self.field, self.message)
+    }
+}
+
+/// The result of importing a Data Dragon dump: every champion that was read, plus a note for each
+/// field that could not be mapped onto `ChampionData` or had to be filled in.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub(crate) champions: Vec<ChampionData>,
+    pub(crate) notes: Vec<ImportNote>,
+}
+
+/// Import a local Data Dragon `champion.json` or `championFull.json` file. Fields Data Dragon does
+/// not provide (attack windup, attack speed ratio, crit chance, abilities and so on) are taken from
+/// `existing` when the champion is already known, and defaulted otherwise.
+pub fn import_data_dragon(path: &Path, existing: Option<&ChampionRegistry>) -> Result<ImportReport, DataError> {
+    let file: DataDragonFile = read_json_file(path)?;
+    let mut report = ImportReport::default();
+
+    for champion in file.data.values() {
+        let previous = existing.and_then(|registry| registry.get(&champion.id));
+        let data = convert_champion(champion, previous, &mut report.notes);
+
+        report.champions.push(data);
+    }
+
+    Ok(report)
+}
+
+fn convert_champion(champion: &DataDragonChampion, previous: Option<&ChampionData>, notes: &mut Vec<ImportNote>) -> ChampionData {
+    let mut note = |field: &str, message: &str| {
+        notes.push(ImportNote {
+            champion: champion.id.clone(),
+            field: field.to_string(),
+            message: message.to_string(),
+        });
+    };
+
+    let resource_type = match champion.partype.as_str() {
+        "Mana" => ResourceType::Mana,
+        "Energy" => ResourceType::Energy,
+        "Fury" | "Rage" | "Ferocity" | "Heat" | "Courage" | "Grit" => ResourceType::Fury,
+        "Health" => ResourceType::Health,
+        "None" | "Blood Well" => ResourceType::None,
+        partype => {
+            note("partype", &format!("unknown resource '{}', defaulted to none", partype));
+            ResourceType::None
+        }
+    };
+
+    let mut stat = |key: &str| -> f32 {
+        match champion.stats.get(key) {
+            Some(value) => *value,
+            None => {
+                note(key, "missing from source, defaulted to 0");
+                0.0
+            }
+        }
+    };
+
+    let mut data = ChampionData {
+        id: champion.id.to_lowercase(),
+        name: champion.name.clone(),
+        base_health: stat("hp"),
+        base_health_growth: stat("hpperlevel"),
+        base_hp5: stat("hpregen"),
+        base_hp5_growth: stat("hpregenperlevel"),
+        base_resource: stat("mp"),
+        base_resource_growth: stat("mpperlevel"),
+        base_rp5: stat("mpregen"),
+        base_rp5_growth: stat("mpregenperlevel"),
+        resource_type,
+        resource_per_hit: 0.0,
+        base_ad: stat("attackdamage"),
+        base_ad_growth: stat("attackdamageperlevel"),
+        base_as: stat("attackspeed"),
+        // Data Dragon stores attack speed growth as a percentage
+        base_as_growth_percent: stat("attackspeedperlevel") / 100.0,
+        attack_windup: DEFAULT_ATTACK_WINDUP,
+        as_ratio: 0.0,
+        base_armor: stat("armor"),
+        base_armor_growth: stat("armorperlevel"),
+        base_mr: stat("spellblock"),
+        base_mr_growth: stat("spellblockperlevel"),
+        base_range: stat("attackrange").round() as i32,
+        base_ms: stat("movespeed").round() as i32,
+        base_crit: stat("crit") / 100.0,
+        training_dummy: false,
+        abilities: Vec::new(),
+        skill_order: None,
+    };
+
+    match previous {
+        Some(previous) => {
+            data.attack_windup = previous.attack_windup;
+            data.as_ratio = previous.as_ratio;
+            // Data Dragon's spell data is not structured enough to convert, so keep ours
+            data.abilities = previous.abilities.clone();
+            data.skill_order = previous.skill_order.clone();
+            data.resource_per_hit = previous.resource_per_hit;
+            // Data Dragon lists a crit of 0 for every champion, so a baseline crit chance is ours too
+            data.base_crit = previous.base_crit;
+            data.training_dummy = previous.training_dummy;
+        }
+        None => {
+            data.as_ratio = data.base_as;
+            note("attack_windup", &format!("not provided by Data Dragon, defaulted to {}", DEFAULT_ATTACK_WINDUP));
+            note("as_ratio", "not provided by Data Dragon, defaulted to base attack speed");
+        }
+    }
+
+    for key in champion.stats.keys() {
+        if !MAPPED_STATS.contains(&key.as_str()) {
+            note(key, "no matching ChampStats field");
+        }
+    }
+
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::champion::stats::ResourceType;
+    use crate::data::champions::ChampionRegistry;
+    use crate::data::data_dir;
+    use crate::data::ddragon::import_data_dragon;
+
+    const DATA_DRAGON_CHAMPIONS: &str = r#"{
+        "type": "champion",
+        "format": "standAloneComplex",
+        "version": "14.3.1",
+        "data": {
+            "Aatrox": {
+                "version": "14.3.1",
+                "id": "Aatrox",
+                "key": "266",
+                "name": "Aatrox",
+                "partype": "Blood Well",
+                "stats": {
+                    "hp": 650, "hpperlevel": 114, "mp": 0, "mpperlevel": 0, "movespeed": 345,
+                    "armor": 38, "armorperlevel": 4.8, "spellblock": 32, "spellblockperlevel": 2.05,
+                    "attackrange": 175, "hpregen": 3, "hpregenperlevel": 1, "mpregen": 0,
+                    "mpregenperlevel": 0, "crit": 0, "critperlevel": 0, "attackdamage": 60,
+                    "attackdamageperlevel": 5, "attackspeedperlevel": 2.5, "attackspeed": 0.651
+                }
+            },
+            "KSante": {
+                "version": "14.3.1",
+                "id": "KSante",
+                "key": "897",
+                "name": "K'Sante",
+                "partype": "Mana",
+                "stats": {
+                    "hp": 625, "hpperlevel": 120, "mp": 320, "mpperlevel": 60, "movespeed": 330,
+                    "armor": 36, "armorperlevel": 5.2, "spellblock": 30, "spellblockperlevel": 2.1,
+                    "attackrange": 150, "hpregen": 9.5, "hpregenperlevel": 1, "mpregen": 7,
+                    "mpregenperlevel": 0.5, "crit": 0, "critperlevel": 0, "attackdamage": 64,
+                    "attackdamageperlevel": 3.5, "attackspeedperlevel": 1.5, "attackspeed": 0.688
+                }
+            }
+        }
+    }"#;
+
+    fn write_fixture(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("league-calculator-{}-{}.json", name, std::process::id()));
+        fs::write(&path, DATA_DRAGON_CHAMPIONS).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_import_data_dragon() {
+        let path = write_fixture("import");
+        let report = import_data_dragon(&path, None).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(report.champions.len(), 2);
+
+        let ksante = report.champions.iter().find(|champion| champion.id == "ksante").unwrap();
+
+        assert_eq!(ksante.name, "K'Sante");
+        assert_eq!(ksante.base_health, 625.0);
+        assert_eq!(ksante.base_resource_growth, 60.0);
+        assert_eq!(ksante.resource_type, ResourceType::Mana);
+        assert_eq!(ksante.base_as, 0.688);
+        assert_eq!(ksante.base_as_growth_percent, 0.015);
+        assert_eq!(ksante.as_ratio, 0.688);
+        assert_eq!(ksante.base_mr_growth, 2.1);
+        assert_eq!(ksante.base_range, 150);
+    }
+
+    #[test]
+    fn test_import_data_dragon_reports_unmapped_fields() {
+        let path = write_fixture("unmapped");
+        let report = import_data_dragon(&path, None).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let aatrox_fields: Vec<&str> = report.notes.iter()
+            .filter(|note| note.champion == "Aatrox")
+            .map(|note| note.field.as_str())
+            .collect();
+
+        assert_eq!(aatrox_fields, vec!["attack_windup", "as_ratio", "critperlevel"]);
+    }
+
+    #[test]
+    fn test_import_data_dragon_keeps_existing_windup() {
+        let path = write_fixture("existing");
+        let existing = ChampionRegistry::load(&data_dir().unwrap().join("champions")).unwrap();
+        let report = import_data_dragon(&path, Some(&existing)).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let aatrox = report.champions.iter().find(|champion| champion.id == "aatrox").unwrap();
+
+        assert_eq!(aatrox.base_health, 650.0);
+        assert_eq!(aatrox.attack_windup, 0.23384);
+        assert_eq!(aatrox.as_ratio, 0.651);
+        assert_eq!(aatrox.base_crit, 0.5);
+        assert_eq!(aatrox.abilities, existing.get("aatrox").unwrap().abilities);
+        assert!(!report.notes.iter().any(|note| note.champion == "Aatrox" && note.field == "attack_windup"));
+    }
+}
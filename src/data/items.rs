@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::path::Path;
+use crate::data::champions::MAX_SUGGESTIONS;
+use crate::data::{read_json_dir, DataError};
+use crate::item::Item;
+use crate::utils::{closest_matches, normalize_name};
+
+/// All known item definitions, keyed by item id. Lookups accept either the numeric id or the
+/// display name, ignoring case and punctuation, so "1036", "Long Sword" and "longsword" all find
+/// the same item.
+#[derive(Debug, Clone, Default)]
+pub struct ItemRegistry {
+    items: HashMap<i32, Item>,
+    aliases: HashMap<String, i32>,
+}
+
+impl ItemRegistry {
+    /// Load every item file (one item per `.json` file) in a directory. Data sets without an
+    /// `items` directory simply have no items.
+    pub fn load(dir: &Path) -> Result<ItemRegistry, DataError> {
+        let mut registry = ItemRegistry::default();
+
+        if !dir.is_dir() {
+            return Ok(registry);
+        }
+
+        for (path, item) in read_json_dir::<Item>(dir)? {
+            if registry.items.contains_key(&item.id) || registry.aliases.contains_key(&normalize_name(&item.name)) {
+                return Err(DataError::Duplicate { id: item.id.to_string(), path });
+            }
+
+            registry.insert(item);
+        }
+
+        Ok(registry)
+    }
+
+    pub fn insert(&mut self, item: Item) {
+        self.aliases.insert(normalize_name(&item.name), item.id);
+        self.items.insert(item.id, item);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Item> {
+        match name.trim().parse::<i32>() {
+            Ok(id) => self.items.get(&id),
+            Err(_) => self.aliases.get(&normalize_name(name)).and_then(|id| self.items.get(id)),
+        }
+    }
+
+    /// Look up an item, suggesting similarly named items when it cannot be found.
+    pub fn find(&self, name: &str) -> Result<&Item, DataError> {
+        match self.get(name) {
+            Some(item) => Ok(item),
+            None => Err(DataError::UnknownItem {
+                name: name.to_string(),
+                suggestions: self.suggestions(name),
+            }),
+        }
+    }
+
+    /// Display names of every item, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.items.values().map(|item| item.name.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    fn suggestions(&self, name: &str) -> Vec<String> {
+        let candidates = self.aliases.iter()
+            .filter_map(|(key, id)| self.items.get(id).map(|item| (vec![key.clone()], item.name.as_str())));
+
+        closest_matches(name, candidates, MAX_SUGGESTIONS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::items::ItemRegistry;
+    use crate::data::{data_dir, DataError};
+    use crate::item::Item;
+
+    #[test]
+    fn test_registry_lookup_by_id_and_name() {
+        let registry = ItemRegistry::load(&data_dir().unwrap().join("items")).unwrap();
+
+        assert_eq!(registry.get("1036").unwrap().name, "Long Sword");
+        assert_eq!(registry.get("long sword").unwrap().id, 1036);
+        assert_eq!(registry.get("Doran's Blade").unwrap().id, 1055);
+        assert_eq!(registry.get("doransblade").unwrap().ad, 8);
+        assert!(registry.get("9999").is_none());
+    }
+
+    #[test]
+    fn test_registry_find_suggests_similar_names() {
+        let registry = ItemRegistry::load(&data_dir().unwrap().join("items")).unwrap();
+
+        match registry.find("Long Swrod") {
+            Err(DataError::UnknownItem { name, suggestions }) => {
+                assert_eq!(name, "Long Swrod");
+                assert_eq!(suggestions, vec![String::from("Long Sword")]);
+            }
+            _ => panic!("Expected an unknown item error"),
+        }
+    }
+
+    #[test]
+    fn test_registry_rejects_duplicate_names() {
+        let dir = std::env::temp_dir().join(format!("league-calculator-items-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.json"), r#"{ "id": 1, "name": "Sword", "cost": 100 }"#).unwrap();
+        std::fs::write(dir.join("b.json"), r#"{ "id": 2, "name": "sword", "cost": 100 }"#).unwrap();
+
+        let result = ItemRegistry::load(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(result, Err(DataError::Duplicate { .. })));
+    }
+
+    #[test]
+    fn test_registry_missing_dir_is_empty() {
+        let registry = ItemRegistry::load(&data_dir().unwrap().join("no-such-dir")).unwrap();
+
+        assert!(registry.names().is_empty());
+    }
+
+    #[test]
+    fn test_load_registry_from_data_dir() {
+        let mut registry = ItemRegistry::load(&data_dir().unwrap().join("items")).unwrap();
+
+        assert!(registry.names().len() >= 14);
+        assert!(registry.names().contains(&"B. F. Sword"));
+
+        let item: Item = serde_json::from_str(r#"{ "id": 9999, "name": "Test Item", "cost": 0 }"#).unwrap();
+        registry.insert(item);
+        assert_eq!(registry.get("test item").unwrap().id, 9999);
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::data::patch::GameData;
+use crate::data::{read_json_file, DataError};
+
+/// A change to a single numeric field of a definition.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldChange {
+    Set(f64),
+    Add(f64),
+    Percent(f64), // +10 is a 10% increase, -10 a 10% decrease
+}
+
+/// A set of hypothetical balance changes applied on top of a data set, e.g. "+5 base AD and -2 armor
+/// growth for Aatrox" or "+5 AD on Long Sword". Only the listed fields are touched.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Overlay {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) champions: BTreeMap<String, BTreeMap<String, FieldChange>>,
+    #[serde(default)]
+    pub(crate) items: BTreeMap<String, BTreeMap<String, FieldChange>>, // Keyed by item name or id
+}
+
+impl FieldChange {
+    pub fn apply(&self, value: f64) -> f64 {
+        match self {
+            FieldChange::Set(new_value) => *new_value,
+            FieldChange::Add(delta) => value + delta,
+            FieldChange::Percent(percent) => value * (1.0 + percent / 100.0),
+        }
+    }
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldChange::Set(value) => write!(f, "= {}", value),
+            FieldChange::Add(delta) => write!(f, "{:+}", delta),
+            FieldChange::Percent(percent) => write!(f, "{:+}%", percent),
+        }
+    }
+}
+
+impl Overlay {
+    pub fn load(path: &Path) -> Result<Overlay, DataError> {
+        read_json_file(path)
+    }
+
+    /// Apply every change to the matching champions and items in `data`. Nothing is changed if any
+    /// entry is invalid.
+    pub fn apply(&self, data: &mut GameData) -> Result<(), DataError> {
+        let mut patched_champions = Vec::new();
+        let mut patched_items = Vec::new();
+
+        for (champion, changes) in &self.champions {
+            let original = data.champions.find(champion)?;
+            patched_champions.push(self.apply_changes(&original.id, original, changes)?);
+        }
+
+        for (item, changes) in &self.items {
+            let original = data.items.find(item)?;
+            patched_items.push(self.apply_changes(item, original, changes)?);
+        }
+
+        for champion in patched_champions {
+            data.champions.insert(champion);
+        }
+
+        for item in patched_items {
+            data.items.insert(item);
+        }
+
+        data.overlays.push(self.to_string());
+
+        Ok(())
+    }
+
+    /// Apply `changes` to a copy of a definition by editing its serialized form, so any numeric
+    /// field can be targeted by the name it has in the data files.
+    fn apply_changes<T: Serialize + DeserializeOwned>(&self, target: &str, original: &T, changes: &BTreeMap<String, FieldChange>) -> Result<T, DataError> {
+        let mut value = serde_json::to_value(original).map_err(|error| self.error(target, "", &error.to_string()))?;
+
+        for (field, change) in changes {
+            let current = match value.get(field) {
+                Some(current) => current,
+                None => return Err(self.error(target, field, "no such field")),
+            };
+
+            let new_value = match (current.as_i64(), current.as_f64()) {
+                (Some(_), Some(number)) => Value::from(change.apply(number).round() as i64),
+                (None, Some(number)) => Value::from(change.apply(as_decimal(number))),
+                _ => return Err(self.error(target, field, "not a numeric field")),
+            };
+
+            value[field] = new_value;
+        }
+
+        serde_json::from_value(value).map_err(|error| self.error(target, "", &error.to_string()))
+    }
+
+    fn error(&self, target: &str, field: &str, message: &str) -> DataError {
+        DataError::Overlay {
+            overlay: self.name.clone(),
+            message: if field.is_empty() {
+                format!("{}: {}", target, message)
+            } else {
+                format!("{}.{}: {}", target, field, message)
+            },
+        }
+    }
+}
+
+/// Float fields are stored as `f32`; going through the shortest decimal representation keeps
+/// results like 4.45 - 2 at 2.45 instead of 2.4499998.
+fn as_decimal(number: f64) -> f64 {
+    (number as f32).to_string().parse().unwrap_or(number)
+}
+
+impl fmt::Display for Overlay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let changes: Vec<String> = self.champions.iter()
+            .chain(self.items.iter())
+            .flat_map(|(target, changes)| {
+                changes.iter().map(move |(field, change)| format!("{}.{} {}", target, field, change))
+            })
+            .collect();
+
+        write!(f, "{} ({})", self.name, changes.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::data_dir;
+    use crate::data::DataError;
+    use crate::data::overlay::{FieldChange, Overlay};
+    use crate::data::patch::GameData;
+
+    const AATROX_BUFF: &str = r#"{
+        "name": "aatrox-buff",
+        "champions": {
+            "Aatrox": {
+                "base_ad": { "add": 5 },
+                "base_armor_growth": { "add": -2 },
+                "base_health": { "percent": 10 },
+                "base_range": { "set": 200 }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_field_change_apply() {
+        assert_eq!(FieldChange::Set(10.0).apply(5.0), 10.0);
+        assert_eq!(FieldChange::Add(-2.0).apply(5.0), 3.0);
+        assert_eq!(FieldChange::Percent(50.0).apply(10.0), 15.0);
+        assert_eq!(FieldChange::Percent(-50.0).apply(10.0), 5.0);
+    }
+
+    #[test]
+    fn test_apply_overlay() {
+        let overlay: Overlay = serde_json::from_str(AATROX_BUFF).unwrap();
+        let mut data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        overlay.apply(&mut data).unwrap();
+
+        let aatrox = data.champions.get("aatrox").unwrap();
+
+        assert_eq!(aatrox.base_ad, 65.0);
+        assert_eq!(aatrox.base_armor_growth, 2.45);
+        assert_eq!(aatrox.base_health, 753.5);
+        assert_eq!(aatrox.base_range, 200);
+        assert_eq!(aatrox.base_mr, 32.0);
+
+        // Other champions are untouched
+        assert_eq!(data.champions.get("test-bruiser").unwrap().base_ad, 60.0);
+    }
+
+    #[test]
+    fn test_apply_overlay_is_recorded() {
+        let overlay: Overlay = serde_json::from_str(AATROX_BUFF).unwrap();
+        let mut data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        overlay.apply(&mut data).unwrap();
+
+        assert_eq!(data.overlays, vec![String::from(
+            "aatrox-buff (Aatrox.base_ad +5, Aatrox.base_armor_growth -2, Aatrox.base_health +10%, Aatrox.base_range = 200)"
+        )]);
+    }
+
+    #[test]
+    fn test_apply_item_overlay() {
+        let overlay: Overlay = serde_json::from_str(r#"{
+            "name": "sword-buff",
+            "items": {
+                "Long Sword": { "ad": { "add": 5 } },
+                "1042": { "attack_speed": { "set": 0.12 } }
+            }
+        }"#).unwrap();
+        let mut data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        overlay.apply(&mut data).unwrap();
+
+        assert_eq!(data.items.get("long sword").unwrap().ad, 15);
+        assert_eq!(data.items.get("dagger").unwrap().as_, 0.12);
+        assert_eq!(data.overlays, vec![String::from("sword-buff (1042.attack_speed = 0.12, Long Sword.ad +5)")]);
+    }
+
+    #[test]
+    fn test_apply_item_overlay_unknown_item() {
+        let overlay: Overlay = serde_json::from_str(r#"{
+            "name": "bad",
+            "items": { "Long Swrod": { "ad": { "add": 5 } } }
+        }"#).unwrap();
+        let mut data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        assert!(matches!(overlay.apply(&mut data), Err(DataError::UnknownItem { .. })));
+    }
+
+    #[test]
+    fn test_apply_overlay_unknown_field() {
+        let overlay: Overlay = serde_json::from_str(r#"{
+            "name": "bad",
+            "champions": { "aatrox": { "base_damage": { "add": 5 } } }
+        }"#).unwrap();
+        let mut data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        match overlay.apply(&mut data) {
+            Err(DataError::Overlay { overlay, message }) => {
+                assert_eq!(overlay, "bad");
+                assert_eq!(message, "aatrox.base_damage: no such field");
+            }
+            _ => panic!("Expected an overlay error"),
+        }
+
+        assert!(data.overlays.is_empty());
+    }
+
+    #[test]
+    fn test_apply_overlay_non_numeric_field() {
+        let overlay: Overlay = serde_json::from_str(r#"{
+            "name": "bad",
+            "champions": { "aatrox": { "name": { "set": 5 } } }
+        }"#).unwrap();
+        let mut data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        assert!(overlay.apply(&mut data).is_err());
+        assert_eq!(data.champions.get("aatrox").unwrap().name, "Aatrox");
+    }
+
+    #[test]
+    fn test_apply_overlay_unknown_champion() {
+        let overlay: Overlay = serde_json::from_str(r#"{
+            "name": "bad",
+            "champions": { "atrox": { "base_ad": { "add": 5 } } }
+        }"#).unwrap();
+        let mut data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        assert!(matches!(overlay.apply(&mut data), Err(DataError::UnknownChampion { .. })));
+    }
+}
@@ -0,0 +1,191 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use crate::champion::Champion;
+use crate::data::champions::ChampionRegistry;
+use crate::data::items::ItemRegistry;
+use crate::data::runes::RuneRegistry;
+use crate::data::DataError;
+use crate::item::Item;
+
+pub(crate) const LIVE_PATCH: &str = "live";
+
+/// One complete set of game data. The live data sits at the root of the data directory; older or
+/// upcoming patches are kept under `patches/<version>` with the same layout. Overlays can be applied
+/// on top with `Overlay::apply`.
+#[derive(Debug, Clone)]
+pub struct GameData {
+    pub(crate) patch: Option<String>,
+    pub(crate) champions: ChampionRegistry,
+    pub(crate) items: ItemRegistry,
+    pub(crate) runes: RuneRegistry,
+    pub(crate) overlays: Vec<String>, // Descriptions of the overlays applied, in order
+}
+
+impl GameData {
+    /// Load the data set for `patch`, or the live data set when no patch is given.
+    pub fn load(root: &Path, patch: Option<&str>) -> Result<GameData, DataError> {
+        let dir = patch_dir(root, patch)?;
+
+        if let Some(patch) = patch {
+            if !dir.is_dir() {
+                return Err(DataError::UnknownPatch {
+                    patch: patch.to_string(),
+                    available: available_patches(root),
+                });
+            }
+        }
+
+        Ok(GameData {
+            patch: patch.map(str::to_string),
+            champions: ChampionRegistry::load(&dir.join("champions"))?,
+            items: ItemRegistry::load(&dir.join("items"))?,
+            runes: RuneRegistry::load(&dir.join("runes"))?,
+            overlays: Vec::new(),
+        })
+    }
+
+    pub fn create_champion(&self, name: &str) -> Result<Champion, DataError> {
+        self.champions.create_champion(name)
+    }
+
+    pub fn find_item(&self, name: &str) -> Result<Item, DataError> {
+        self.items.find(name).cloned()
+    }
+}
+
+/// The directory holding the data set for `patch`, or the live data set when no patch is given. The
+/// patch must be a single directory name, so it cannot point outside of `patches/`.
+pub fn patch_dir(root: &Path, patch: Option<&str>) -> Result<PathBuf, DataError> {
+    match patch {
+        Some(patch) => {
+            let mut components = Path::new(patch).components();
+
+            match (components.next(), components.next()) {
+                (Some(Component::Normal(_)), None) => Ok(root.join("patches").join(patch)),
+                _ => Err(DataError::InvalidPatch { patch: patch.to_string() }),
+            }
+        }
+        None => Ok(root.to_path_buf()),
+    }
+}
+
+/// Every patch version with a data set under `patches/`, sorted by version number.
+pub fn available_patches(root: &Path) -> Vec<String> {
+    let mut patches = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(root.join("patches")) {
+        for entry in entries.flatten() {
+            if entry.path().is_dir() {
+                patches.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+
+    patches.sort_by(|a, b| compare_patches(a, b));
+    patches
+}
+
+/// Order patch versions by their dot-separated parts, numerically where both parts are numbers, so
+/// "14.3" comes before "14.10".
+fn compare_patches(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.split('.');
+    let mut b_parts = b.split('.');
+
+    loop {
+        let ordering = match (a_parts.next(), b_parts.next()) {
+            (Some(a), Some(b)) => match (a.parse::<u32>(), b.parse::<u32>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => a.cmp(b),
+            },
+            (a, b) => return a.is_some().cmp(&b.is_some()),
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::data_dir;
+    use crate::data::DataError;
+    use crate::data::patch::{available_patches, patch_dir, GameData};
+
+    #[test]
+    fn test_load_live_data() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        assert_eq!(data.patch, None);
+        assert_eq!(data.create_champion("aatrox").unwrap().champ_stats.health, 685.0);
+    }
+
+    #[test]
+    fn test_load_patch_data() {
+        let data = GameData::load(&data_dir().unwrap(), Some("14.3")).unwrap();
+
+        assert_eq!(data.patch.as_deref(), Some("14.3"));
+        assert_eq!(data.create_champion("aatrox").unwrap().champ_stats.health, 650.0);
+
+        // Patch champions have their abilities, so fights on both patches can cast them
+        let live = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        for champion in ["aatrox", "test-bruiser"] {
+            let abilities = live.create_champion(champion).unwrap().abilities.len();
+            assert_eq!(data.create_champion(champion).unwrap().abilities.len(), abilities, "{}", champion);
+        }
+    }
+
+    #[test]
+    fn test_load_items() {
+        let live = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let patch = GameData::load(&data_dir().unwrap(), Some("14.3")).unwrap();
+
+        assert_eq!(live.find_item("long sword").unwrap().ad, 10);
+        assert_eq!(patch.find_item("1036").unwrap().name, "Long Sword");
+        assert!(live.find_item("long swrod").is_err());
+    }
+
+    #[test]
+    fn test_load_unknown_patch() {
+        let error = GameData::load(&data_dir().unwrap(), Some("1.0")).unwrap_err();
+
+        match error {
+            DataError::UnknownPatch { patch, available } => {
+                assert_eq!(patch, "1.0");
+                assert!(available.contains(&String::from("14.3")));
+            }
+            _ => panic!("Expected an unknown patch error"),
+        }
+    }
+
+    #[test]
+    fn test_patch_dir_rejects_paths() {
+        let root = data_dir().unwrap();
+
+        assert_eq!(patch_dir(&root, Some("14.3")).unwrap(), root.join("patches").join("14.3"));
+        assert_eq!(patch_dir(&root, None).unwrap(), root);
+
+        for patch in ["..", "../..", "14.3/../..", "/tmp", ".", ""] {
+            assert!(matches!(patch_dir(&root, Some(patch)), Err(DataError::InvalidPatch { .. })), "{}", patch);
+            assert!(matches!(GameData::load(&root, Some(patch)), Err(DataError::InvalidPatch { .. })), "{}", patch);
+        }
+    }
+
+    #[test]
+    fn test_available_patches() {
+        assert!(available_patches(&data_dir().unwrap()).contains(&String::from("14.3")));
+
+        let root = std::env::temp_dir().join(format!("league-calculator-patches-{}", std::process::id()));
+
+        for patch in ["14.10", "14.3", "9.23", "14.3.1"] {
+            std::fs::create_dir_all(root.join("patches").join(patch)).unwrap();
+        }
+
+        let patches = available_patches(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(patches, vec!["9.23", "14.3", "14.3.1", "14.10"]);
+    }
+}
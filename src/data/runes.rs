@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::path::Path;
+use serde::Deserialize;
+use crate::data::champions::MAX_SUGGESTIONS;
+use crate::data::{read_json_dir, DataError};
+use crate::rune::{Rune, RunePage, RuneTree, KEYSTONE_SLOT, MINOR_SLOTS, SHARD_ROWS};
+use crate::utils::{closest_matches, normalize_name};
+
+/// The on-disk layout of a rune tree: one file per tree listing every rune in it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuneTreeData {
+    pub(crate) tree: RuneTree,
+    pub(crate) runes: Vec<RuneEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuneEntry {
+    pub(crate) id: i32,
+    pub(crate) name: String,
+    pub(crate) slot: u8,
+}
+
+/// All known runes. Like items, runes can be looked up by numeric id or by name.
+#[derive(Debug, Clone, Default)]
+pub struct RuneRegistry {
+    runes: HashMap<i32, Rune>,
+    aliases: HashMap<String, i32>,
+}
+
+impl RuneTreeData {
+    pub fn to_runes(&self) -> Vec<Rune> {
+        self.runes.iter()
+            .map(|entry| Rune {
+                id: entry.id,
+                name: entry.name.clone(),
+                tree: self.tree,
+                slot: entry.slot,
+            })
+            .collect()
+    }
+}
+
+impl RuneRegistry {
+    /// Load every rune tree file in a directory. Data sets without a `runes` directory simply have
+    /// no runes.
+    pub fn load(dir: &Path) -> Result<RuneRegistry, DataError> {
+        let mut registry = RuneRegistry::default();
+
+        if !dir.is_dir() {
+            return Ok(registry);
+        }
+
+        for (path, tree) in read_json_dir::<RuneTreeData>(dir)? {
+            for rune in tree.to_runes() {
+                if registry.runes.contains_key(&rune.id) || registry.aliases.contains_key(&normalize_name(&rune.name)) {
+                    return Err(DataError::Duplicate { id: rune.id.to_string(), path });
+                }
+
+                registry.insert(rune);
+            }
+        }
+
+        Ok(registry)
+    }
+
+    pub fn insert(&mut self, rune: Rune) {
+        self.aliases.insert(normalize_name(&rune.name), rune.id);
+        self.runes.insert(rune.id, rune);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Rune> {
+        match name.trim().parse::<i32>() {
+            Ok(id) => self.runes.get(&id),
+            Err(_) => self.aliases.get(&normalize_name(name)).and_then(|id| self.runes.get(id)),
+        }
+    }
+
+    /// Look up a rune, suggesting similarly named runes when it cannot be found.
+    pub fn find(&self, name: &str) -> Result<&Rune, DataError> {
+        match self.get(name) {
+            Some(rune) => Ok(rune),
+            None => Err(DataError::UnknownRune {
+                name: name.to_string(),
+                suggestions: self.suggestions(name),
+            }),
+        }
+    }
+
+    /// Check that a page could be picked in game: a keystone, one rune from each minor row of the
+    /// keystone's tree, two runes from different minor rows of another tree, and stat shards that
+    /// belong to their rows.
+    pub fn validate_page(&self, page: &RunePage) -> Result<(), DataError> {
+        let error = |message: String| DataError::RunePage { page: page.name.clone(), message };
+
+        let keystone = self.find(&page.keystone)?;
+
+        if keystone.slot != KEYSTONE_SLOT {
+            return Err(error(format!("{} is not a keystone", keystone.name)));
+        }
+
+        for (rune, slot) in page.primary.iter().zip(MINOR_SLOTS) {
+            let rune = self.find(rune)?;
+
+            if rune.tree != keystone.tree || rune.slot != slot {
+                return Err(error(format!("{} is not in row {} of {}", rune.name, slot, keystone.tree)));
+            }
+        }
+
+        let first = self.find(&page.secondary[0])?;
+        let second = self.find(&page.secondary[1])?;
+
+        for rune in [first, second] {
+            if rune.slot == KEYSTONE_SLOT {
+                return Err(error(format!("{} is a keystone and cannot be a secondary rune", rune.name)));
+            }
+
+            if rune.tree == keystone.tree {
+                return Err(error(format!("{} is in the primary tree ({})", rune.name, keystone.tree)));
+            }
+        }
+
+        if first.tree != second.tree {
+            return Err(error(format!("{} and {} are in different trees", first.name, second.name)));
+        }
+
+        if first.slot == second.slot {
+            return Err(error(format!("{} and {} are in the same row", first.name, second.name)));
+        }
+
+        for (row, (shard, allowed)) in page.shards.iter().zip(SHARD_ROWS).enumerate() {
+            if !allowed.contains(shard) {
+                return Err(error(format!("{:?} is not allowed in shard row {}", shard, row + 1)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn suggestions(&self, name: &str) -> Vec<String> {
+        let candidates = self.aliases.iter()
+            .filter_map(|(key, id)| self.runes.get(id).map(|rune| (vec![key.clone()], rune.name.as_str())));
+
+        closest_matches(name, candidates, MAX_SUGGESTIONS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::runes::RuneRegistry;
+    use crate::data::{data_dir, read_json_file, DataError};
+    use crate::rune::{RunePage, RuneTree, StatShard, KEYSTONE_SLOT};
+
+    fn registry() -> RuneRegistry {
+        RuneRegistry::load(&data_dir().unwrap().join("runes")).unwrap()
+    }
+
+    fn page() -> RunePage {
+        read_json_file(&data_dir().unwrap().join("rune-pages").join("aatrox-conqueror.json")).unwrap()
+    }
+
+    #[test]
+    fn test_registry_lookup() {
+        let registry = registry();
+
+        assert_eq!(registry.get("conqueror").unwrap().tree, RuneTree::Precision);
+        assert_eq!(registry.get("8112").unwrap().name, "Electrocute");
+        assert_eq!(registry.get("Legend: Alacrity").unwrap().slot, 2);
+        assert_eq!(registry.get("grasp of the undying").unwrap().slot, KEYSTONE_SLOT);
+        assert!(matches!(registry.find("Conquerer"), Err(DataError::UnknownRune { .. })));
+    }
+
+    #[test]
+    fn test_validate_page() {
+        assert!(registry().validate_page(&page()).is_ok());
+    }
+
+    #[test]
+    fn test_validate_page_rejects_wrong_rows() {
+        let registry = registry();
+
+        let mut page = page();
+        page.keystone = String::from("Triumph");
+        assert!(matches!(registry.validate_page(&page), Err(DataError::RunePage { .. })));
+
+        let mut page = self::page();
+        page.primary.swap(0, 1);
+        assert!(registry.validate_page(&page).is_err());
+
+        let mut page = self::page();
+        page.shards[0] = StatShard::Armor;
+        assert!(registry.validate_page(&page).is_err());
+    }
+
+    #[test]
+    fn test_validate_page_rejects_bad_secondary() {
+        let registry = registry();
+
+        // Same tree as the primary runes
+        let mut page = page();
+        page.secondary = [String::from("Overheal"), String::from("Cut Down")];
+        assert!(registry.validate_page(&page).is_err());
+
+        // Same row
+        let mut page = self::page();
+        page.secondary = [String::from("Second Wind"), String::from("Bone Plating")];
+        assert!(registry.validate_page(&page).is_err());
+
+        // Different trees
+        let mut page = self::page();
+        page.secondary = [String::from("Bone Plating"), String::from("Cheap Shot")];
+        assert!(registry.validate_page(&page).is_err());
+    }
+}
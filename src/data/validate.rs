@@ -0,0 +1,606 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use serde::de::DeserializeOwned;
+use crate::ability::Ability;
+use crate::data::champions::ChampionData;
+use crate::data::overlay::Overlay;
+use crate::data::patch::{available_patches, GameData};
+use crate::data::runes::RuneTreeData;
+use crate::data::{json_files, read_json_file, DataError};
+use crate::effects::DamageFormula;
+use crate::item::Item;
+use crate::rune::{RunePage, MINOR_SLOTS};
+use crate::utils::normalize_name;
+
+// Sanity limits; anything outside of these is almost certainly a typo
+const MAX_ATTACK_SPEED: f32 = 2.5;
+const MAX_ITEM_ATTACK_SPEED: f32 = 1.0;
+
+/// A single problem with a data file. `field` is empty for problems with the file as a whole.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub(crate) path: PathBuf,
+    pub(crate) field: String,
+    pub(crate) message: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub(crate) files: usize,
+    pub(crate) problems: Vec<Problem>,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "{}: {}", self.path.display(), self.message)
+        } else {
+            write!(f, "{}: {}: {}", self.path.display(), self.field, self.message)
+        }
+    }
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    fn problem(&mut self, path: &Path, field: &str, message: &str) {
+        self.problems.push(Problem {
+            path: path.to_path_buf(),
+            field: field.to_string(),
+            message: message.to_string(),
+        });
+    }
+}
+
+/// Check every data file under `root`: the live data, every patch, every overlay and every rune
+/// page. Unlike loading, validation does not stop at the first problem.
+pub fn validate_data(root: &Path) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    validate_data_set(root, &mut report);
+
+    for patch in available_patches(root) {
+        validate_data_set(&root.join("patches").join(patch), &mut report);
+    }
+
+    let overlays = root.join("overlays");
+
+    if overlays.is_dir() {
+        validate_overlays(root, &overlays, &mut report);
+    }
+
+    let rune_pages = root.join("rune-pages");
+
+    if rune_pages.is_dir() {
+        validate_rune_pages(root, &rune_pages, &mut report);
+    }
+
+    report
+}
+
+fn validate_data_set(dir: &Path, report: &mut ValidationReport) {
+    validate_files(&dir.join("champions"), report, champion_problems, |data: &ChampionData| vec![data.id.clone()]);
+
+    let items = dir.join("items");
+
+    if items.is_dir() {
+        validate_files(&items, report, item_problems, |item: &Item| vec![item.id.to_string(), item.name.clone()]);
+    }
+
+    let runes = dir.join("runes");
+
+    if runes.is_dir() {
+        validate_files(&runes, report, rune_tree_problems, |tree: &RuneTreeData| {
+            tree.runes.iter().flat_map(|rune| [rune.id.to_string(), rune.name.clone()]).collect()
+        });
+    }
+}
+
+/// Parse and check each file in `dir`. Files whose `keys` (ids or names) were already used by an
+/// earlier file are reported as duplicates.
+fn validate_files<T: DeserializeOwned>(dir: &Path, report: &mut ValidationReport,
+                                       problems: fn(&T) -> Vec<(&'static str, String)>,
+                                       keys: fn(&T) -> Vec<String>) {
+    let paths = match json_files(dir) {
+        Ok(paths) => paths,
+        Err(error) => {
+            report.problem(dir, "", &error.to_string());
+            return;
+        }
+    };
+
+    let mut seen: HashMap<String, PathBuf> = HashMap::new();
+
+    for path in paths {
+        report.files += 1;
+
+        let value: T = match read_json_file(&path) {
+            Ok(value) => value,
+            Err(error) => {
+                report.problem(&path, "", &error_message(&error));
+                continue;
+            }
+        };
+
+        for (field, message) in problems(&value) {
+            report.problem(&path, field, &message);
+        }
+
+        for key in keys(&value) {
+            if let Some(first) = seen.insert(normalize_name(&key), path.clone()) {
+                report.problem(&path, "", &format!("'{}' is already used by {}", key, first.display()));
+            }
+        }
+    }
+}
+
+fn validate_overlays(root: &Path, dir: &Path, report: &mut ValidationReport) {
+    let paths = match json_files(dir) {
+        Ok(paths) => paths,
+        Err(error) => {
+            report.problem(dir, "", &error.to_string());
+            return;
+        }
+    };
+
+    // Overlays are checked against the live data; if that does not load it was already reported
+    let live = GameData::load(root, None).ok();
+
+    for path in paths {
+        report.files += 1;
+
+        let overlay = match Overlay::load(&path) {
+            Ok(overlay) => overlay,
+            Err(error) => {
+                report.problem(&path, "", &error_message(&error));
+                continue;
+            }
+        };
+
+        if let Some(mut data) = live.clone() {
+            if let Err(error) = overlay.apply(&mut data) {
+                report.problem(&path, "", &error.to_string());
+            }
+        }
+    }
+}
+
+fn validate_rune_pages(root: &Path, dir: &Path, report: &mut ValidationReport) {
+    let paths = match json_files(dir) {
+        Ok(paths) => paths,
+        Err(error) => {
+            report.problem(dir, "", &error.to_string());
+            return;
+        }
+    };
+
+    // Like overlays, pages are checked against the live data
+    let live = GameData::load(root, None).ok();
+
+    for path in paths {
+        report.files += 1;
+
+        let page: RunePage = match read_json_file(&path) {
+            Ok(page) => page,
+            Err(error) => {
+                report.problem(&path, "", &error_message(&error));
+                continue;
+            }
+        };
+
+        if let Some(data) = &live {
+            if let Err(error) = data.runes.validate_page(&page) {
+                report.problem(&path, "", &error.to_string());
+            }
+        }
+    }
+}
+
+/// File errors without the path, which the problem already shows.
+fn error_message(error: &DataError) -> String {
+    match error {
+        DataError::Parse { source, .. } => source.to_string(),
+        DataError::Io { source, .. } => source.to_string(),
+        _ => error.to_string(),
+    }
+}
+
+fn champion_problems(data: &ChampionData) -> Vec<(&'static str, String)> {
+    let mut problems = Vec::new();
+
+    if data.id.trim().is_empty() {
+        problems.push(("id", String::from("must not be empty")));
+    }
+
+    if data.name.trim().is_empty() {
+        problems.push(("name", String::from("must not be empty")));
+    }
+
+    if data.base_health <= 0.0 {
+        problems.push(("base_health", format!("must be positive, got {}", data.base_health)));
+    }
+
+    let non_negative = [
+        ("base_health_growth", data.base_health_growth),
+        ("base_hp5", data.base_hp5),
+        ("base_hp5_growth", data.base_hp5_growth),
+        ("base_resource", data.base_resource),
+        ("base_resource_growth", data.base_resource_growth),
+        ("base_rp5", data.base_rp5),
+        ("base_rp5_growth", data.base_rp5_growth),
+        ("resource_per_hit", data.resource_per_hit),
+        ("base_ad", data.base_ad),
+        ("base_ad_growth", data.base_ad_growth),
+        ("base_as_growth_percent", data.base_as_growth_percent),
+        ("base_armor", data.base_armor),
+        ("base_armor_growth", data.base_armor_growth),
+        ("base_mr", data.base_mr),
+        ("base_mr_growth", data.base_mr_growth),
+    ];
+
+    for (field, value) in non_negative {
+        if value < 0.0 || !value.is_finite() {
+            problems.push((field, format!("must not be negative, got {}", value)));
+        }
+    }
+
+    if !(0.0..=1.0).contains(&data.base_crit) {
+        problems.push(("base_crit", format!("must be between 0 and 1, got {}", data.base_crit)));
+    }
+
+    let mut slots = Vec::new();
+
+    for ability in &data.abilities {
+        if slots.contains(&ability.slot) {
+            problems.push(("abilities", format!("{}: defined more than once", ability.slot)));
+        }
+
+        slots.push(ability.slot);
+        problems.extend(ability_problems(ability).into_iter().map(|message| ("abilities", format!("{}: {}", ability.slot, message))));
+    }
+
+    // Training dummies never attack or move, so their attack stats are allowed to be zero
+    if data.training_dummy {
+        return problems;
+    }
+
+    if data.base_as <= 0.0 || data.base_as > MAX_ATTACK_SPEED {
+        problems.push(("base_as", format!("must be between 0 and {}, got {}", MAX_ATTACK_SPEED, data.base_as)));
+    }
+
+    if data.as_ratio <= 0.0 || data.as_ratio > MAX_ATTACK_SPEED {
+        problems.push(("as_ratio", format!("must be between 0 and {}, got {}", MAX_ATTACK_SPEED, data.as_ratio)));
+    }
+
+    if data.attack_windup <= 0.0 || data.attack_windup > 1.0 {
+        problems.push(("attack_windup", format!("must be between 0 and 1, got {}", data.attack_windup)));
+    }
+
+    if data.base_range <= 0 {
+        problems.push(("base_range", format!("must be positive, got {}", data.base_range)));
+    }
+
+    if data.base_ms <= 0 {
+        problems.push(("base_ms", format!("must be positive, got {}", data.base_ms)));
+    }
+
+    problems
+}
+
+fn ability_problems(ability: &Ability) -> Vec<String> {
+    let mut problems = Vec::new();
+    let ranks = ability.max_rank();
+
+    if ability.name.trim().is_empty() {
+        problems.push(String::from("name must not be empty"));
+    }
+
+    if ranks == 0 {
+        problems.push(String::from("must have at least one rank of base_damage, cooldown or cost"));
+    }
+
+    for (field, values) in [("base_damage", &ability.base_damage), ("cooldown", &ability.cooldown), ("cost", &ability.cost)] {
+        if !values.is_empty() && values.len() != ranks {
+            problems.push(format!("{} has {} ranks, expected {}", field, values.len(), ranks));
+        }
+
+        if let Some(value) = values.iter().find(|value| **value < 0.0 || !value.is_finite()) {
+            problems.push(format!("{} must not be negative, got {}", field, value));
+        }
+    }
+
+    for (field, value) in formula_terms(&ability.ratios).into_iter().chain([("cast_time", ability.cast_time)]) {
+        if value < 0.0 || !value.is_finite() {
+            problems.push(format!("{} must not be negative, got {}", field, value));
+        }
+    }
+
+    if let Some(hit_chance) = ability.hit_chance.filter(|hit_chance| !(0.0..=1.0).contains(hit_chance)) {
+        problems.push(format!("hit_chance must be between 0 and 1, got {}", hit_chance));
+    }
+
+    if let Some(crowd_control) = ability.crowd_control.filter(|crowd_control| crowd_control.duration <= 0.0) {
+        problems.push(format!("crowd_control duration must be positive, got {}", crowd_control.duration));
+    }
+
+    if let Some(refund) = ability.cooldown_refund.filter(|refund| refund.on_hit < 0.0 || refund.on_kill < 0.0) {
+        problems.push(format!("cooldown_refund must not be negative, got {} on hit and {} on kill", refund.on_hit, refund.on_kill));
+    }
+
+    if let Some(shield) = ability.shield {
+        if shield.duration <= 0.0 {
+            problems.push(format!("shield duration must be positive, got {}", shield.duration));
+        }
+
+        if let Some(decay) = shield.decay.filter(|decay| decay.delay < 0.0 || decay.delay >= shield.duration) {
+            problems.push(format!("shield decay delay must be between 0 and the duration of {}, got {}", shield.duration, decay.delay));
+        }
+    }
+
+    if ability.range < 0 {
+        problems.push(format!("range must not be negative, got {}", ability.range));
+    }
+
+    problems
+}
+
+fn formula_terms(formula: &DamageFormula) -> [(&'static str, f32); 9] {
+    [
+        ("flat", formula.flat),
+        ("ad", formula.ad),
+        ("bonus_ad", formula.bonus_ad),
+        ("ap", formula.ap),
+        ("max_health", formula.max_health),
+        ("bonus_health", formula.bonus_health),
+        ("target_current_health", formula.target_current_health),
+        ("target_max_health", formula.target_max_health),
+        ("target_missing_health", formula.target_missing_health),
+    ]
+}
+
+fn item_problems(item: &Item) -> Vec<(&'static str, String)> {
+    let mut problems = Vec::new();
+
+    if item.id <= 0 {
+        problems.push(("id", format!("must be positive, got {}", item.id)));
+    }
+
+    if item.name.trim().is_empty() {
+        problems.push(("name", String::from("must not be empty")));
+    }
+
+    let non_negative = [
+        ("cost", item.cost),
+        ("health", item.health),
+        ("hp5", item.hp5),
+        ("hp5_percent", item.hp5_percent),
+        ("mana", item.mana),
+        ("mp5", item.mp5),
+        ("ad", item.ad),
+        ("ap", item.ap),
+        ("armor", item.armor),
+        ("mr", item.mr),
+        ("range", item.range),
+        ("ms", item.ms),
+        ("tenacity", item.tenacity),
+        ("ability_haste", item.ability_haste),
+        ("ultimate_haste", item.ultimate_haste),
+    ];
+
+    for (field, value) in non_negative {
+        if value < 0 {
+            problems.push((field, format!("must not be negative, got {}", value)));
+        }
+    }
+
+    if !(0.0..=MAX_ITEM_ATTACK_SPEED).contains(&item.as_) {
+        problems.push(("attack_speed", format!("must be between 0 and {}, got {}", MAX_ITEM_ATTACK_SPEED, item.as_)));
+    }
+
+    let vamp = [
+        ("life_steal", item.life_steal),
+        ("omnivamp", item.omnivamp),
+        ("physical_vamp", item.physical_vamp),
+    ];
+
+    for (field, value) in vamp {
+        if !(0.0..=1.0).contains(&value) {
+            problems.push((field, format!("must be between 0 and 1, got {}", value)));
+        }
+    }
+
+    if item.tenacity > 100 {
+        problems.push(("tenacity", format!("must be at most 100, got {}", item.tenacity)));
+    }
+
+    if let Some(on_hit) = &item.on_hit {
+        for (field, value) in formula_terms(&on_hit.damage) {
+            if value < 0.0 || !value.is_finite() {
+                problems.push(("on_hit", format!("{} must not be negative, got {}", field, value)));
+            }
+        }
+    }
+
+    if let Some(dot) = &item.dot {
+        for (field, value) in formula_terms(&dot.damage) {
+            if value < 0.0 || !value.is_finite() {
+                problems.push(("dot", format!("{} must not be negative, got {}", field, value)));
+            }
+        }
+
+        if dot.duration <= 0.0 || !dot.duration.is_finite() {
+            problems.push(("dot", format!("duration must be positive, got {}", dot.duration)));
+        }
+
+        if let Some(max_stacks) = dot.max_stacks.filter(|max_stacks| *max_stacks < 1) {
+            problems.push(("dot", format!("max_stacks must be at least 1, got {}", max_stacks)));
+        }
+    }
+
+    problems
+}
+
+fn rune_tree_problems(tree: &RuneTreeData) -> Vec<(&'static str, String)> {
+    let mut problems = Vec::new();
+
+    for rune in &tree.runes {
+        if rune.id <= 0 {
+            problems.push(("runes", format!("{}: id must be positive, got {}", rune.name, rune.id)));
+        }
+
+        if rune.name.trim().is_empty() {
+            problems.push(("runes", format!("{}: name must not be empty", rune.id)));
+        }
+
+        if rune.slot > MINOR_SLOTS[MINOR_SLOTS.len() - 1] {
+            problems.push(("runes", format!("{}: slot must be between 0 and 3, got {}", rune.name, rune.slot)));
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use crate::ability::CooldownRefund;
+    use crate::data::champions::ChampionData;
+    use crate::data::data_dir;
+    use crate::data::validate::{champion_problems, item_problems, rune_tree_problems, validate_data};
+    use crate::item::Item;
+
+    fn temp_data_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("league-calculator-validate-{}-{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("champions")).unwrap();
+        fs::create_dir_all(dir.join("items")).unwrap();
+        dir
+    }
+
+    fn champion(name: &str) -> ChampionData {
+        let mut data = crate::data::champion_registry().unwrap().get("test-bruiser").unwrap().clone();
+        data.id = name.to_string();
+        data.name = name.to_string();
+        data
+    }
+
+    #[test]
+    fn test_shipped_data_is_valid() {
+        let report = validate_data(&data_dir().unwrap());
+
+        assert!(report.is_valid(), "{:?}", report.problems);
+        assert!(report.files > 0);
+    }
+
+    #[test]
+    fn test_champion_problems() {
+        let mut data = champion("test");
+        assert!(champion_problems(&data).is_empty());
+
+        data.base_as = 0.0;
+        data.attack_windup = 1.5;
+        data.base_armor_growth = -1.0;
+
+        let fields: Vec<&str> = champion_problems(&data).into_iter().map(|(field, _)| field).collect();
+
+        assert_eq!(fields, vec!["base_armor_growth", "base_as", "attack_windup"]);
+    }
+
+    #[test]
+    fn test_ability_problems() {
+        let mut data = champion("test");
+        let mut ability = data.abilities[0].clone();
+        ability.cooldown.pop();
+        ability.ratios.ad = -1.0;
+        ability.cooldown_refund = Some(CooldownRefund { on_hit: -2.0, on_kill: 0.0 });
+        data.abilities.push(ability);
+
+        let problems = champion_problems(&data);
+
+        assert_eq!(problems, vec![
+            ("abilities", String::from("Q: defined more than once")),
+            ("abilities", String::from("Q: cooldown has 4 ranks, expected 5")),
+            ("abilities", String::from("Q: ad must not be negative, got -1")),
+            ("abilities", String::from("Q: cooldown_refund must not be negative, got -2 on hit and 0 on kill")),
+        ]);
+    }
+
+    #[test]
+    fn test_training_dummy_may_not_attack() {
+        let mut data = champion("dummy");
+        data.base_as = 0.0;
+        data.as_ratio = 0.0;
+        data.attack_windup = 0.0;
+        data.base_range = 0;
+        data.base_ms = 0;
+
+        assert_eq!(champion_problems(&data).len(), 5);
+
+        data.training_dummy = true;
+
+        assert!(champion_problems(&data).is_empty());
+    }
+
+    #[test]
+    fn test_item_problems() {
+        let mut item: Item = serde_json::from_str(r#"{ "id": 1036, "name": "Long Sword", "cost": 350 }"#).unwrap();
+        assert!(item_problems(&item).is_empty());
+
+        item.ad = -10;
+        item.as_ = 25.0;
+        item.life_steal = 8.0;
+
+        let fields: Vec<&str> = item_problems(&item).into_iter().map(|(field, _)| field).collect();
+
+        assert_eq!(fields, vec!["ad", "attack_speed", "life_steal"]);
+
+        let item: Item = serde_json::from_str(r#"{ "id": 6653, "name": "Liandry's Torment", "cost": 3000,
+            "dot": { "damage": { "target_max_health": -0.02 }, "damage_type": "magical", "duration": 0.0, "max_stacks": 0 } }"#).unwrap();
+        let problems: Vec<String> = item_problems(&item).into_iter().map(|(field, problem)| format!("{}: {}", field, problem)).collect();
+
+        assert_eq!(problems, vec![
+            "dot: target_max_health must not be negative, got -0.02",
+            "dot: duration must be positive, got 0",
+            "dot: max_stacks must be at least 1, got 0",
+        ]);
+    }
+
+    #[test]
+    fn test_rune_tree_problems() {
+        let tree = serde_json::from_str(r#"{
+            "tree": "precision",
+            "runes": [{ "id": 8010, "name": "Conqueror", "slot": 0 }, { "id": 1, "name": "Bad", "slot": 4 }]
+        }"#).unwrap();
+
+        let problems = rune_tree_problems(&tree);
+
+        assert_eq!(problems, vec![("runes", String::from("Bad: slot must be between 0 and 3, got 4"))]);
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let dir = temp_data_dir("problems");
+        let mut bad = champion("bad");
+        bad.base_as = 0.0;
+
+        fs::write(dir.join("champions/bad.json"), serde_json::to_string(&bad).unwrap()).unwrap();
+        fs::write(dir.join("champions/copy.json"), serde_json::to_string(&champion("Bad")).unwrap()).unwrap();
+        fs::write(dir.join("champions/missing.json"), r#"{ "id": "missing", "name": "Missing" }"#).unwrap();
+        fs::write(dir.join("items/sword.json"), r#"{ "id": 1, "name": "Sword", "cost": -5 }"#).unwrap();
+
+        let report = validate_data(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let problems: Vec<String> = report.problems.iter().map(|problem| problem.to_string()).collect();
+
+        assert_eq!(report.files, 4);
+        assert_eq!(problems.len(), 4, "{:?}", problems);
+        assert!(problems[0].ends_with("bad.json: base_as: must be between 0 and 2.5, got 0"));
+        assert!(problems[1].contains("copy.json: 'Bad' is already used by"));
+        assert!(problems[2].contains("missing.json: missing field"));
+        assert!(problems[3].ends_with("sword.json: cost: must not be negative, got -5"));
+    }
+}
@@ -1,14 +1,69 @@
 use std::ops::Rem;
 use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::champion::stats::ChampStats;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DamageType {
     Physical,
     Magical,
     True,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrowdControlType {
+    Slow,
+    Root,
+    Stun,
+    KnockUp,
+}
+
+/// Crowd control applied by an ability when it hits.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CrowdControl {
+    pub(crate) kind: CrowdControlType,
+    pub(crate) duration: f32, // In seconds, before tenacity
+}
+
+/// Damage of an ability, on-hit or over-time effect. Source ratios scale with the champion applying
+/// the effect, target ratios with the health of the champion taking it, and `min`/`max` cap the total,
+/// e.g. the limit on percent health damage against monsters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DamageFormula {
+    pub(crate) flat: f32,
+    pub(crate) ad: f32,
+    pub(crate) bonus_ad: f32,
+    pub(crate) ap: f32,
+    pub(crate) max_health: f32,
+    pub(crate) bonus_health: f32,
+    pub(crate) target_current_health: f32,
+    pub(crate) target_max_health: f32,
+    pub(crate) target_missing_health: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) min: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max: Option<f32>,
+}
+
+/// When an on-hit or over-time effect is applied to the enemy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectTrigger {
+    #[default]
+    Attack,
+    Ability,
+    Aura, // Every tick while both champions are alive, e.g. Sunfire Aegis
+}
+
+/// Number of uses of an on-hit effect that never runs out, e.g. from an item.
+pub const UNLIMITED_USES: i32 = i32::MAX;
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::enum_variant_names)]
 pub enum EffectTickRate {
     PerSecond = 30,
     PerHalfSecond = 15,
@@ -19,50 +74,60 @@ pub enum EffectTickRate {
 #[derive(Debug, Clone)]
 pub struct DoTEffect {
     pub(crate) id: String,
-    pub(crate) damage_over_time: f32,
+    pub(crate) damage_over_time: DamageFormula,
     pub(crate) damage_type: DamageType,
     pub(crate) damage_time_left: Duration,
     pub(crate) tick_rate: EffectTickRate,
     pub(crate) effect_time_left: Duration,
     pub(crate) finite_time_left: bool,
+    pub(crate) trigger: EffectTrigger,
 }
 
 #[derive(Debug, Clone)]
 pub struct LimitedUseOnHitEffect {
     pub(crate) id: String,
-    pub(crate) damage: f32,
+    pub(crate) damage: DamageFormula,
     pub(crate) damage_type: DamageType,
     pub(crate) num_uses: i32,
     pub(crate) effect_time_left: Duration,
     pub(crate) finite_time_left: bool,
 }
 
+/// Every stack deals `damage_over_time` each second until `damage_time_left` runs out, which a new
+/// stack refreshes.
 #[derive(Debug, Clone)]
 pub struct StackingOnHitEffect {
     pub(crate) id: String,
-    pub(crate) damage_over_time: f32,
+    pub(crate) damage_over_time: DamageFormula,
     pub(crate) damage_type: DamageType,
     pub(crate) damage_time_left: Duration,
     pub(crate) max_stacks: i32,
     pub(crate) current_stacks: i32,
     pub(crate) effect_time_left: Duration,
-    pub(crate) finite_time_left: bool
+    pub(crate) finite_time_left: bool,
+    pub(crate) trigger: EffectTrigger,
 }
 
 impl StackingOnHitEffect {
-    pub(crate) fn new(id: &str, damage_over_time: f32, damage_type: DamageType, max_stacks: i32, damage_time_left: Duration, effect_time_left: Duration, finite_time_left: bool) -> Self {
+    pub(crate) fn new(id: &str, damage_over_time: impl Into<DamageFormula>, damage_type: DamageType, max_stacks: i32, damage_time_left: Duration, effect_time_left: Duration, finite_time_left: bool) -> Self {
         StackingOnHitEffect {
             id: id.to_string(),
-            damage_over_time,
+            damage_over_time: damage_over_time.into(),
             damage_type,
             max_stacks,
             current_stacks: 0,
             effect_time_left,
             damage_time_left,
             finite_time_left,
+            trigger: EffectTrigger::Attack,
         }
     }
 
+    pub fn with_trigger(mut self, trigger: EffectTrigger) -> Self {
+        self.trigger = trigger;
+        self
+    }
+
     pub fn set_current_stacks(&mut self, current_stacks: i32) {
         self.current_stacks = current_stacks;
     }
@@ -72,7 +137,7 @@ impl StackingOnHitEffect {
     }
 
     pub fn reduce_damage_time_left(&mut self, time: Duration) {
-        self.damage_time_left -= time;
+        self.damage_time_left = self.damage_time_left.saturating_sub(time);
     }
 
     pub fn set_effect_time_left(&mut self, time_left: Duration) {
@@ -80,50 +145,59 @@ impl StackingOnHitEffect {
     }
 
     pub fn reduce_effect_time_left(&mut self, time: Duration) {
-        self.effect_time_left -= time;
+        self.effect_time_left = self.effect_time_left.saturating_sub(time);
     }
 
-    pub fn reduce_current_stacks(&mut self) {
-        self.current_stacks -= 1;
+    /// The effect as applied by `source`, with the source's stats fixed at the time of the hit.
+    pub fn snapshot(&self, source: &ChampStats) -> StackingOnHitEffect {
+        StackingOnHitEffect { damage_over_time: self.damage_over_time.snapshot(source), ..self.clone() }
     }
 }
 
 impl DoTEffect {
-    pub(crate) fn new(id: &str, damage_over_time: f32, damage_type: DamageType, time_left: Duration,
+    pub(crate) fn new(id: &str, damage_over_time: impl Into<DamageFormula>, damage_type: DamageType, time_left: Duration,
                       tick_rate: EffectTickRate, effect_time_left: Duration, finite_time_left: bool) -> Self {
         DoTEffect {
             id: id.to_string(),
-            damage_over_time,
+            damage_over_time: damage_over_time.into(),
             damage_type,
             damage_time_left: time_left,
             tick_rate,
             effect_time_left,
             finite_time_left,
+            trigger: EffectTrigger::Attack,
         }
     }
 
+    pub fn with_trigger(mut self, trigger: EffectTrigger) -> Self {
+        self.trigger = trigger;
+        self
+    }
+
     pub fn set_damage_time_left(&mut self, time_left: Duration) {
         self.damage_time_left = time_left;
     }
 
     pub fn reduce_damage_time_left(&mut self, time: Duration) {
-        self.damage_time_left -= time;
+        self.damage_time_left = self.damage_time_left.saturating_sub(time);
     }
 
-    pub fn set_effect_time_left(&mut self, time_left: Duration) {
-        self.effect_time_left = time_left;
+    pub fn reduce_effect_time_left(&mut self, time: Duration) {
+        self.effect_time_left = self.effect_time_left.saturating_sub(time);
     }
 
-    pub fn reduce_effect_time_left(&mut self, time: Duration) {
-        self.effect_time_left -= time;
+    /// The effect as applied by `source`. Later ticks keep the source's stats from the time of the
+    /// hit, while target ratios use the target's health at each tick.
+    pub fn snapshot(&self, source: &ChampStats) -> DoTEffect {
+        DoTEffect { damage_over_time: self.damage_over_time.snapshot(source), ..self.clone() }
     }
 }
 
 impl LimitedUseOnHitEffect {
-    pub(crate) fn new(id: &str, damage: f32, damage_type: DamageType, num_uses: i32, effect_time_left: Duration, finite_time_left: bool) -> Self {
+    pub(crate) fn new(id: &str, damage: impl Into<DamageFormula>, damage_type: DamageType, num_uses: i32, effect_time_left: Duration, finite_time_left: bool) -> Self {
         LimitedUseOnHitEffect {
             id: id.to_string(),
-            damage,
+            damage: damage.into(),
             damage_type,
             num_uses,
             effect_time_left,
@@ -135,12 +209,62 @@ impl LimitedUseOnHitEffect {
         self.num_uses -= 1;
     }
 
-    pub fn set_effect_time_left(&mut self, time_left: Duration) {
-        self.effect_time_left = time_left;
+    pub fn reduce_effect_time_left(&mut self, time: Duration) {
+        self.effect_time_left = self.effect_time_left.saturating_sub(time);
     }
+}
 
-    pub fn reduce_effect_time_left(&mut self, time: Duration) {
-        self.effect_time_left -= time;
+impl DamageFormula {
+    pub fn flat(amount: f32) -> DamageFormula {
+        DamageFormula { flat: amount, ..DamageFormula::default() }
+    }
+
+    /// Damage before mitigation dealt by a champion with `source` stats to one with `target` stats.
+    pub fn amount(&self, source: &ChampStats, target: &ChampStats) -> f32 {
+        self.snapshot(source).target_amount(target)
+    }
+
+    /// Fold the ratios on `source` into the flat damage.
+    pub fn snapshot(&self, source: &ChampStats) -> DamageFormula {
+        DamageFormula {
+            flat: self.flat
+                + self.ad * source.ad as f32
+                + self.bonus_ad * source.bonus_ad
+                + self.ap * source.ap
+                + self.max_health * source.max_health
+                + self.bonus_health * source.bonus_health,
+            ad: 0.0,
+            bonus_ad: 0.0,
+            ap: 0.0,
+            max_health: 0.0,
+            bonus_health: 0.0,
+            ..*self
+        }
+    }
+
+    /// Damage from the flat part and the target ratios, within the caps. Source ratios are ignored;
+    /// use `snapshot` first to include them.
+    pub fn target_amount(&self, target: &ChampStats) -> f32 {
+        let mut amount = self.flat
+            + self.target_current_health * target.health.max(0.0)
+            + self.target_max_health * target.max_health
+            + self.target_missing_health * target.missing_health();
+
+        if let Some(min) = self.min {
+            amount = amount.max(min);
+        }
+
+        if let Some(max) = self.max {
+            amount = amount.min(max);
+        }
+
+        amount
+    }
+}
+
+impl From<f32> for DamageFormula {
+    fn from(amount: f32) -> DamageFormula {
+        DamageFormula::flat(amount)
     }
 }
 
@@ -162,14 +286,20 @@ impl PartialEq for StackingOnHitEffect {
     }
 }
 
-impl PartialEq for DamageType {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (DamageType::Physical, DamageType::Physical) => true,
-            (DamageType::Magical, DamageType::Magical) => true,
-            (DamageType::True, DamageType::True) => true,
-            _ => false,
-        }
+impl CrowdControlType {
+    /// Whether the target cannot move, which makes skillshots impossible to dodge.
+    pub fn immobilizes(&self) -> bool {
+        !matches!(self, CrowdControlType::Slow)
+    }
+
+    /// Whether the target cannot attack or cast.
+    pub fn disables(&self) -> bool {
+        matches!(self, CrowdControlType::Stun | CrowdControlType::KnockUp)
+    }
+
+    /// Knock-ups are the only crowd control that tenacity does not shorten.
+    pub fn reduced_by_tenacity(&self) -> bool {
+        !matches!(self, CrowdControlType::KnockUp)
     }
 }
 
@@ -184,18 +314,6 @@ impl EffectTickRate {
     }
 }
 
-impl PartialEq for EffectTickRate {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (EffectTickRate::PerSecond, EffectTickRate::PerSecond) => true,
-            (EffectTickRate::PerHalfSecond, EffectTickRate::PerHalfSecond) => true,
-            (EffectTickRate::PerQuarterSecond, EffectTickRate::PerQuarterSecond) => true,
-            (EffectTickRate::PerHalfQuarterSecond, EffectTickRate::PerHalfQuarterSecond) => true,
-            _ => false,
-        }
-    }
-}
-
 impl Rem<&EffectTickRate> for i32 {
     type Output = i32;
 
@@ -207,14 +325,15 @@ impl Rem<&EffectTickRate> for i32 {
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
-    use crate::effects::{DamageType, DoTEffect, EffectTickRate, LimitedUseOnHitEffect, StackingOnHitEffect};
+    use crate::effects::{DamageFormula, DamageType, DoTEffect, EffectTickRate, LimitedUseOnHitEffect, StackingOnHitEffect};
+    use crate::utils::create_champion_by_name;
 
     #[test]
     fn test_dot_effect_new() {
         let dot_effect = DoTEffect::new("burn", 5.0, DamageType::Magical, Duration::from_secs(5), EffectTickRate::PerSecond, Duration::from_secs(10), true);
 
         assert_eq!(dot_effect.id, "burn");
-        assert_eq!(dot_effect.damage_over_time, 5.0);
+        assert_eq!(dot_effect.damage_over_time, DamageFormula::flat(5.0));
         assert_eq!(dot_effect.damage_type, DamageType::Magical);
         assert_eq!(dot_effect.damage_time_left, Duration::from_secs(5));
         assert_eq!(dot_effect.tick_rate, EffectTickRate::PerSecond);
@@ -241,7 +360,7 @@ mod tests {
         let limited_use_on_hit_effect = LimitedUseOnHitEffect::new("test", 50.0, DamageType::True, 5, Duration::from_secs(10), true);
 
         assert_eq!(limited_use_on_hit_effect.id, "test");
-        assert_eq!(limited_use_on_hit_effect.damage, 50.0);
+        assert_eq!(limited_use_on_hit_effect.damage, DamageFormula::flat(50.0));
         assert_eq!(limited_use_on_hit_effect.damage_type, DamageType::True);
         assert_eq!(limited_use_on_hit_effect.num_uses, 5);
     }
@@ -259,7 +378,7 @@ mod tests {
         let stacking_on_hit_effect = StackingOnHitEffect::new("test", 5.0, DamageType::True, 5, Duration::from_secs(5), Duration::from_secs(10), true);
 
         assert_eq!(stacking_on_hit_effect.id, "test");
-        assert_eq!(stacking_on_hit_effect.damage_over_time, 5.0);
+        assert_eq!(stacking_on_hit_effect.damage_over_time, DamageFormula::flat(5.0));
         assert_eq!(stacking_on_hit_effect.damage_type, DamageType::True);
         assert_eq!(stacking_on_hit_effect.max_stacks, 5);
         assert_eq!(stacking_on_hit_effect.current_stacks, 0);
@@ -290,15 +409,6 @@ mod tests {
         assert_eq!(stacking_on_hit_effect.damage_time_left, Duration::from_secs(3));
     }
 
-    #[test]
-    fn test_stacking_on_hit_effect_reduce_current_stacks() {
-        let mut stacking_on_hit_effect = StackingOnHitEffect::new("test", 5.0, DamageType::True, 5, Duration::from_secs(5), Duration::from_secs(10), true);
-        stacking_on_hit_effect.set_current_stacks(3);
-        stacking_on_hit_effect.reduce_current_stacks();
-
-        assert_eq!(stacking_on_hit_effect.current_stacks, 2);
-    }
-
     #[test]
     fn test_stacking_on_hit_effect_reduce_effect_time_left() {
         let mut stacking_on_hit_effect = StackingOnHitEffect::new("test", 5.0, DamageType::True, 5, Duration::from_secs(5), Duration::from_secs(10), true);
@@ -315,14 +425,6 @@ mod tests {
         assert_eq!(stacking_on_hit_effect.effect_time_left, Duration::from_secs(10));
     }
 
-    #[test]
-    fn test_dot_effect_set_effect_time_left() {
-        let mut dot_effect = DoTEffect::new("burn", 5.0, DamageType::Magical, Duration::from_secs(5), EffectTickRate::PerSecond, Duration::from_secs(10), true);
-        dot_effect.set_effect_time_left(Duration::from_secs(10));
-
-        assert_eq!(dot_effect.effect_time_left, Duration::from_secs(10));
-    }
-
     #[test]
     fn test_dot_effect_reduce_effect_time_left() {
         let mut dot_effect = DoTEffect::new("burn", 5.0, DamageType::Magical, Duration::from_secs(5), EffectTickRate::PerSecond, Duration::from_secs(10), true);
@@ -332,19 +434,63 @@ mod tests {
     }
 
     #[test]
-    fn test_limited_use_on_hit_effect_set_effect_time_left() {
+    fn test_limited_use_on_hit_effect_reduce_effect_time_left() {
         let mut limited_use_on_hit_effect = LimitedUseOnHitEffect::new("test", 50.0, DamageType::True, 5, Duration::from_secs(10), true);
-        limited_use_on_hit_effect.set_effect_time_left(Duration::from_secs(10));
+        limited_use_on_hit_effect.reduce_effect_time_left(Duration::from_secs(2));
 
-        assert_eq!(limited_use_on_hit_effect.effect_time_left, Duration::from_secs(10));
+        assert_eq!(limited_use_on_hit_effect.effect_time_left, Duration::from_secs(8));
     }
 
     #[test]
-    fn test_limited_use_on_hit_effect_reduce_effect_time_left() {
-        let mut limited_use_on_hit_effect = LimitedUseOnHitEffect::new("test", 50.0, DamageType::True, 5, Duration::from_secs(10), true);
-        limited_use_on_hit_effect.reduce_effect_time_left(Duration::from_secs(2));
+    fn test_damage_formula_target_health() {
+        let source = create_champion_by_name("test-bruiser").unwrap();
+        let mut target = create_champion_by_name("dummy").unwrap();
+        target.champ_stats.health = 4000.0;
 
-        assert_eq!(limited_use_on_hit_effect.effect_time_left, Duration::from_secs(8));
+        let current = DamageFormula { target_current_health: 0.1, min: Some(15.0), ..DamageFormula::default() };
+        assert_eq!(current.amount(&source.champ_stats, &target.champ_stats), 400.0);
+
+        let missing = DamageFormula { flat: 10.0, target_missing_health: 0.05, ..DamageFormula::default() };
+        assert_eq!(missing.amount(&source.champ_stats, &target.champ_stats), 310.0);
+
+        let max = DamageFormula { target_max_health: 0.02, ..DamageFormula::default() };
+        assert_eq!(max.amount(&source.champ_stats, &target.champ_stats), 200.0);
+
+        // The caps apply to the total
+        let capped = DamageFormula { max: Some(60.0), ..current };
+        assert_eq!(capped.amount(&source.champ_stats, &target.champ_stats), 60.0);
+
+        target.champ_stats.health = 100.0;
+        assert_eq!(current.amount(&source.champ_stats, &target.champ_stats), 15.0);
+    }
+
+    #[test]
+    fn test_damage_formula_source_ratios() {
+        let source = create_champion_by_name("test-bruiser").unwrap();
+        let target = create_champion_by_name("dummy").unwrap();
+        let stats = &source.champ_stats;
+
+        let formula = DamageFormula { flat: 20.0, ad: 0.5, ap: 0.3, max_health: 0.02, bonus_health: 0.01, ..DamageFormula::default() };
+        let expected = 20.0 + 0.5 * stats.ad as f32 + 0.3 * stats.ap + 0.02 * stats.max_health + 0.01 * stats.bonus_health;
+
+        assert_eq!(formula.amount(stats, &target.champ_stats), expected);
+        assert_eq!(formula.snapshot(stats), DamageFormula::flat(expected));
+    }
+
+    #[test]
+    fn test_dot_effect_snapshot() {
+        let mut source = create_champion_by_name("test-bruiser").unwrap();
+        let formula = DamageFormula { bonus_ad: 1.0, target_max_health: 0.01, ..DamageFormula::default() };
+        let dot_effect = DoTEffect::new("burn", formula, DamageType::Magical, Duration::from_secs(5), EffectTickRate::PerSecond, Duration::from_secs(10), true);
+
+        source.champ_stats.bonus_ad = 40.0;
+        let applied = dot_effect.snapshot(&source.champ_stats);
+        source.champ_stats.bonus_ad = 0.0;
+
+        assert_eq!(applied.damage_over_time.flat, 40.0);
+        assert_eq!(applied.damage_over_time.bonus_ad, 0.0);
+        assert_eq!(applied.damage_over_time.target_max_health, 0.01);
+        assert_eq!(applied.damage_time_left, Duration::from_secs(5));
     }
 }
 
@@ -484,7 +630,7 @@ mod struct_tests {
     fn test_debug_dot_effect_struct() {
         let dot_effect = DoTEffect::new("burn", 5.0, DamageType::Magical, Duration::from_secs(5), EffectTickRate::PerSecond, Duration::from_secs(10), true);
 
-        assert_eq!(format!("{:?}", dot_effect), "DoTEffect { id: \"burn\", damage_over_time: 5.0, damage_type: Magical, damage_time_left: 5s, tick_rate: PerSecond, effect_time_left: 10s, finite_time_left: true }");
+        assert_eq!(format!("{:?}", dot_effect), "DoTEffect { id: \"burn\", damage_over_time: DamageFormula { flat: 5.0, ad: 0.0, bonus_ad: 0.0, ap: 0.0, max_health: 0.0, bonus_health: 0.0, target_current_health: 0.0, target_max_health: 0.0, target_missing_health: 0.0, min: None, max: None }, damage_type: Magical, damage_time_left: 5s, tick_rate: PerSecond, effect_time_left: 10s, finite_time_left: true, trigger: Attack }");
     }
 
     #[test]
@@ -499,7 +645,7 @@ mod struct_tests {
     fn test_debug_limited_use_on_hit_effect_struct() {
         let limited_use_on_hit_effect = LimitedUseOnHitEffect::new("test", 50.0, DamageType::True, 5, Duration::from_secs(10), true);
 
-        assert_eq!(format!("{:?}", limited_use_on_hit_effect), "LimitedUseOnHitEffect { id: \"test\", damage: 50.0, damage_type: True, num_uses: 5, effect_time_left: 10s, finite_time_left: true }");
+        assert_eq!(format!("{:?}", limited_use_on_hit_effect), "LimitedUseOnHitEffect { id: \"test\", damage: DamageFormula { flat: 50.0, ad: 0.0, bonus_ad: 0.0, ap: 0.0, max_health: 0.0, bonus_health: 0.0, target_current_health: 0.0, target_max_health: 0.0, target_missing_health: 0.0, min: None, max: None }, damage_type: True, num_uses: 5, effect_time_left: 10s, finite_time_left: true }");
     }
 
     #[test]
@@ -514,7 +660,7 @@ mod struct_tests {
     fn test_debug_stacking_on_hit_effect_struct() {
         let stacking_on_hit_effect = StackingOnHitEffect::new("test", 5.0, DamageType::True, 5, Duration::from_secs(5), Duration::from_secs(10), true);
 
-        assert_eq!(format!("{:?}", stacking_on_hit_effect), "StackingOnHitEffect { id: \"test\", damage_over_time: 5.0, damage_type: True, damage_time_left: 5s, max_stacks: 5, current_stacks: 0, effect_time_left: 10s, finite_time_left: true }");
+        assert_eq!(format!("{:?}", stacking_on_hit_effect), "StackingOnHitEffect { id: \"test\", damage_over_time: DamageFormula { flat: 5.0, ad: 0.0, bonus_ad: 0.0, ap: 0.0, max_health: 0.0, bonus_health: 0.0, target_current_health: 0.0, target_max_health: 0.0, target_missing_health: 0.0, min: None, max: None }, damage_type: True, damage_time_left: 5s, max_stacks: 5, current_stacks: 0, effect_time_left: 10s, finite_time_left: true, trigger: Attack }");
     }
 
     #[test]
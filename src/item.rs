@@ -1,17 +1,96 @@
+use serde::{Deserialize, Serialize};
+use crate::effects::{DamageFormula, DamageType, EffectTrigger};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Item {
-    name: String,
-    cost: i32,
-    health: i32,
-    hp5: i32,
-    mana: i32,
-    mp5: i32,
-    ad: i32,
-    as_: f32,
-    armor: i32,
-    mr: i32,
-    range: i32,
-    ms: i32,
-
-    // TODO: Add effects
+    pub(crate) id: i32,
+    pub(crate) name: String,
+    pub(crate) cost: i32,
+    #[serde(default)]
+    pub(crate) health: i32,
+    #[serde(default)]
+    pub(crate) hp5: i32,
+    #[serde(default)]
+    pub(crate) hp5_percent: i32, // Increase of the base health regeneration, in percent
+    #[serde(default)]
+    pub(crate) mana: i32,
+    #[serde(default)]
+    pub(crate) mp5: i32,
+    #[serde(default)]
+    pub(crate) ad: i32,
+    #[serde(default)]
+    pub(crate) ap: i32,
+    #[serde(default, rename = "attack_speed")]
+    pub(crate) as_: f32, // Bonus attack speed as a fraction, e.g. 0.25 for 25%
+    #[serde(default)]
+    pub(crate) armor: i32,
+    #[serde(default)]
+    pub(crate) mr: i32,
+    #[serde(default)]
+    pub(crate) range: i32,
+    #[serde(default)]
+    pub(crate) ms: i32,
+    #[serde(default)]
+    pub(crate) tenacity: i32, // In percent
+    #[serde(default)]
+    pub(crate) ability_haste: i32,
+    #[serde(default)]
+    pub(crate) ultimate_haste: i32,
+    #[serde(default)]
+    pub(crate) life_steal: f32, // As a fraction, like attack speed
+    #[serde(default)]
+    pub(crate) omnivamp: f32,
+    #[serde(default)]
+    pub(crate) physical_vamp: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) on_hit: Option<ItemOnHit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) dot: Option<ItemDot>,
+
     // TODO: Consider activatable items
-}
\ No newline at end of file
+}
+
+/// Damage added to every attack, e.g. Blade of The Ruined King.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemOnHit {
+    pub(crate) damage: DamageFormula,
+    pub(crate) damage_type: DamageType,
+}
+
+/// Damage dealt each second for `duration` seconds after the trigger, e.g. Liandry's Torment burning
+/// enemies hit by abilities. With `max_stacks`, every trigger adds a stack and each stack deals the
+/// damage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemDot {
+    #[serde(default)]
+    pub(crate) trigger: EffectTrigger,
+    pub(crate) damage: DamageFormula,
+    pub(crate) damage_type: DamageType,
+    pub(crate) duration: f32, // In seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_stacks: Option<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::item::Item;
+
+    #[test]
+    fn test_parse_item_with_missing_stats() {
+        let item: Item = serde_json::from_str(r#"{ "id": 1042, "name": "Dagger", "cost": 300, "attack_speed": 0.1 }"#).unwrap();
+
+        assert_eq!(item.as_, 0.1);
+        assert_eq!(item.health, 0);
+        assert_eq!(item.ad, 0);
+    }
+
+    #[test]
+    fn test_parse_item_rejects_unknown_fields() {
+        let item = serde_json::from_str::<Item>(r#"{ "id": 1042, "name": "Dagger", "cost": 300, "as": 0.1 }"#);
+
+        assert!(item.is_err());
+    }
+}
@@ -0,0 +1,506 @@
+use std::time::Duration;
+use crate::ability::AbilitySlot;
+use crate::champion::stats::ChampStats;
+use crate::constants::TICK_SECOND;
+use crate::damage::Damage;
+use crate::effects::{DamageType, StackingOnHitEffect};
+use crate::rune::ADAPTIVE_FORCE_AD_RATIO;
+use crate::utils::normalize_name;
+
+// Conqueror: attacks grant stacks of adaptive force; at max stacks, heal for part of the damage dealt
+const CONQUEROR_MAX_STACKS: i32 = 12;
+const CONQUEROR_DURATION: f32 = 5.0;
+const CONQUEROR_ADAPTIVE_PER_STACK: (f32, f32) = (1.8, 4.0); // At levels 1 and 18
+const CONQUEROR_MELEE_HEAL: f32 = 0.08;
+const CONQUEROR_RANGED_HEAL: f32 = 0.05;
+
+// Lethal Tempo: attacks grant stacks of attack speed; at max stacks, attacks deal bonus damage and
+// attack speed can go over the cap
+const LETHAL_TEMPO_MAX_STACKS: i32 = 6;
+const LETHAL_TEMPO_DURATION: f32 = 6.0;
+const LETHAL_TEMPO_MELEE_AS_PER_STACK: (f32, f32) = (0.04, 0.12);
+const LETHAL_TEMPO_RANGED_AS_PER_STACK: (f32, f32) = (0.03, 0.09);
+const LETHAL_TEMPO_DAMAGE: (f32, f32) = (6.0, 24.0);
+const LETHAL_TEMPO_BONUS_AD_RATIO: f32 = 0.1;
+
+// Press the Attack: 3 attacks in a row deal bonus damage and expose the target
+const PRESS_THE_ATTACK_HITS: i32 = 3;
+const PRESS_THE_ATTACK_WINDOW: f32 = 4.0;
+const PRESS_THE_ATTACK_DAMAGE: (f32, f32) = (40.0, 160.0);
+const PRESS_THE_ATTACK_EXPOSED_DURATION: f32 = 5.0;
+const PRESS_THE_ATTACK_EXPOSED_AMP: f32 = 0.08;
+
+// Electrocute: 3 separate hits within the window deal bonus damage. Every attack is a separate hit,
+// an ability only counts once
+const ELECTROCUTE_HITS: i32 = 3;
+const ELECTROCUTE_WINDOW: f32 = 3.0;
+const ELECTROCUTE_DAMAGE: (f32, f32) = (30.0, 220.0);
+const ELECTROCUTE_BONUS_AD_RATIO: f32 = 0.1;
+const ELECTROCUTE_AP_RATIO: f32 = 0.05;
+const ELECTROCUTE_COOLDOWN: (f32, f32) = (25.0, 20.0);
+
+// Grasp of the Undying: every 4 seconds in combat, the next attack deals bonus damage, heals and
+// permanently grants health. Ranged champions get 60% of the effect
+const GRASP_CHARGE_TIME: f32 = 4.0;
+const GRASP_MAX_HEALTH_DAMAGE: f32 = 0.035;
+const GRASP_MAX_HEALTH_HEAL: f32 = 0.013;
+const GRASP_PERMANENT_HEALTH: f32 = 5.0;
+const GRASP_RANGED_MULTIPLIER: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Keystone {
+    Conqueror,
+    LethalTempo,
+    PressTheAttack,
+    Electrocute,
+    GraspOfTheUndying,
+}
+
+/// What hit the target: an auto attack or the ability in a slot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitSource {
+    Attack,
+    Ability(AbilitySlot),
+}
+
+/// The bonus damage (before mitigation) and healing from one hit.
+#[derive(Debug, Clone, Copy)]
+pub struct KeystoneHit {
+    pub(crate) damage: Damage,
+    pub(crate) healing: f32,
+}
+
+/// A keystone's state during a fight, kept on the champion that owns it. Stacks (and the time until
+/// they fall off) are tracked with a `StackingOnHitEffect`.
+#[derive(Debug, Clone)]
+pub struct KeystoneState {
+    pub(crate) keystone: Keystone,
+    pub(crate) stacks: StackingOnHitEffect,
+    pub(crate) cooldown_left: Duration,
+    pub(crate) charge_time: Duration, // Time in combat since Grasp was last used
+    pub(crate) exposed_time_left: Duration, // Press the Attack's damage amp on the target
+    pub(crate) bonus_ad: f32, // Stats currently granted by stacks
+    pub(crate) bonus_ap: f32,
+    pub(crate) bonus_as: f32,
+    pub(crate) damage_dealt: Damage, // After mitigation
+    pub(crate) healing_done: f32,
+    pub(crate) last_hit: Option<KeystoneHit>, // What the latest hit triggered, after mitigation
+    pub(crate) abilities_hit: Vec<AbilitySlot>, // Abilities that already added an Electrocute stack
+}
+
+impl Keystone {
+    /// The keystone for a rune name, or `None` for keystones without an implementation.
+    pub fn from_name(name: &str) -> Option<Keystone> {
+        match normalize_name(name).as_str() {
+            "conqueror" => Some(Keystone::Conqueror),
+            "lethaltempo" => Some(Keystone::LethalTempo),
+            "presstheattack" => Some(Keystone::PressTheAttack),
+            "electrocute" => Some(Keystone::Electrocute),
+            "graspoftheundying" => Some(Keystone::GraspOfTheUndying),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Keystone::Conqueror => "Conqueror",
+            Keystone::LethalTempo => "Lethal Tempo",
+            Keystone::PressTheAttack => "Press the Attack",
+            Keystone::Electrocute => "Electrocute",
+            Keystone::GraspOfTheUndying => "Grasp of the Undying",
+        }
+    }
+
+    fn max_stacks(&self) -> i32 {
+        match self {
+            Keystone::Conqueror => CONQUEROR_MAX_STACKS,
+            Keystone::LethalTempo => LETHAL_TEMPO_MAX_STACKS,
+            Keystone::PressTheAttack => PRESS_THE_ATTACK_HITS,
+            Keystone::Electrocute => ELECTROCUTE_HITS,
+            Keystone::GraspOfTheUndying => 0,
+        }
+    }
+
+    /// How long stacks last after the last hit.
+    fn stack_duration(&self) -> Duration {
+        Duration::from_secs_f32(match self {
+            Keystone::Conqueror => CONQUEROR_DURATION,
+            Keystone::LethalTempo => LETHAL_TEMPO_DURATION,
+            Keystone::PressTheAttack => PRESS_THE_ATTACK_WINDOW,
+            Keystone::Electrocute => ELECTROCUTE_WINDOW,
+            Keystone::GraspOfTheUndying => 0.0,
+        })
+    }
+}
+
+impl KeystoneState {
+    pub fn new(keystone: Keystone) -> KeystoneState {
+        KeystoneState {
+            keystone,
+            stacks: StackingOnHitEffect::new(keystone.name(), 0.0, DamageType::True, keystone.max_stacks(), Duration::ZERO, Duration::ZERO, true),
+            cooldown_left: Duration::ZERO,
+            charge_time: Duration::ZERO,
+            exposed_time_left: Duration::ZERO,
+            bonus_ad: 0.0,
+            bonus_ap: 0.0,
+            bonus_as: 0.0,
+            damage_dealt: Damage::new(0.0, 0.0, 0.0),
+            healing_done: 0.0,
+            last_hit: None,
+            abilities_hit: Vec::new(),
+        }
+    }
+
+    /// Multiplier for damage dealt to the target, from Press the Attack's exposure.
+    pub fn damage_amp(&self) -> f32 {
+        if self.exposed_time_left > Duration::ZERO { 1.0 + PRESS_THE_ATTACK_EXPOSED_AMP } else { 1.0 }
+    }
+
+    /// Update stacks for an attack or ability that hit an enemy champion. Stat changes are written to
+    /// the bonus stats in `stats`; the caller recalculates the totals. Conqueror and Electrocute react to
+    /// abilities, the other keystones only to attacks.
+    pub fn on_hit(&mut self, stats: &mut ChampStats, level: i32, max_health: f32, source: HitSource) -> KeystoneHit {
+        let mut hit = KeystoneHit { damage: Damage::new(0.0, 0.0, 0.0), healing: 0.0 };
+        let melee = stats.is_melee();
+
+        if source != HitSource::Attack && !matches!(self.keystone, Keystone::Conqueror | Keystone::Electrocute) {
+            return hit;
+        }
+
+        match self.keystone {
+            Keystone::Conqueror => {
+                self.add_stacks(if melee { 2 } else { 1 });
+                self.set_stack_stats(stats, level);
+            }
+            Keystone::LethalTempo => {
+                let at_max_stacks = self.stacks.current_stacks == self.stacks.max_stacks;
+                self.add_stacks(1);
+                self.set_stack_stats(stats, level);
+
+                if at_max_stacks {
+                    let damage = by_level(LETHAL_TEMPO_DAMAGE, level) + LETHAL_TEMPO_BONUS_AD_RATIO * stats.bonus_ad;
+                    hit.damage = adaptive_damage(stats, damage);
+                }
+            }
+            Keystone::PressTheAttack => {
+                // The target cannot be stacked again while it is exposed
+                if self.exposed_time_left == Duration::ZERO {
+                    self.add_stacks(1);
+
+                    if self.stacks.current_stacks == self.stacks.max_stacks {
+                        hit.damage = adaptive_damage(stats, by_level(PRESS_THE_ATTACK_DAMAGE, level));
+                        self.exposed_time_left = Duration::from_secs_f32(PRESS_THE_ATTACK_EXPOSED_DURATION);
+                        self.stacks.set_current_stacks(0);
+                    }
+                }
+            }
+            Keystone::Electrocute => {
+                let repeated = match source {
+                    HitSource::Ability(slot) => self.abilities_hit.contains(&slot),
+                    HitSource::Attack => false,
+                };
+
+                if self.cooldown_left == Duration::ZERO && !repeated {
+                    self.add_stacks(1);
+
+                    if let HitSource::Ability(slot) = source {
+                        self.abilities_hit.push(slot);
+                    }
+
+                    if self.stacks.current_stacks == self.stacks.max_stacks {
+                        let damage = by_level(ELECTROCUTE_DAMAGE, level) + ELECTROCUTE_BONUS_AD_RATIO * stats.bonus_ad + ELECTROCUTE_AP_RATIO * stats.ap;
+                        hit.damage = adaptive_damage(stats, damage);
+                        self.cooldown_left = Duration::from_secs_f32(by_level(ELECTROCUTE_COOLDOWN, level));
+                        self.stacks.set_current_stacks(0);
+                        self.abilities_hit.clear();
+                    }
+                }
+            }
+            Keystone::GraspOfTheUndying => {
+                if self.charge_time >= Duration::from_secs_f32(GRASP_CHARGE_TIME) {
+                    let multiplier = if melee { 1.0 } else { GRASP_RANGED_MULTIPLIER };
+
+                    hit.damage = Damage::new(0.0, GRASP_MAX_HEALTH_DAMAGE * multiplier * max_health, 0.0);
+                    hit.healing = GRASP_MAX_HEALTH_HEAL * multiplier * max_health;
+
+                    stats.add_max_health(GRASP_PERMANENT_HEALTH * multiplier);
+
+                    self.charge_time = Duration::ZERO;
+                }
+            }
+        }
+
+        hit
+    }
+
+    /// Healing from the damage a hit dealt after mitigation (Conqueror at max stacks).
+    pub fn healing_from_damage(&self, stats: &ChampStats, damage_dealt: f32) -> f32 {
+        if self.keystone != Keystone::Conqueror || self.stacks.current_stacks < self.stacks.max_stacks {
+            return 0.0;
+        }
+
+        damage_dealt * if stats.is_melee() { CONQUEROR_MELEE_HEAL } else { CONQUEROR_RANGED_HEAL }
+    }
+
+    /// Advance timers by one tick. Returns true if stacks fell off and the stats need to be
+    /// recalculated.
+    pub fn tick(&mut self, stats: &mut ChampStats, level: i32) -> bool {
+        let tick = Duration::from_secs_f32(TICK_SECOND);
+
+        self.cooldown_left = self.cooldown_left.saturating_sub(tick);
+        self.exposed_time_left = self.exposed_time_left.saturating_sub(tick);
+        self.charge_time += tick;
+
+        if self.stacks.current_stacks == 0 {
+            return false;
+        }
+
+        self.stacks.effect_time_left = self.stacks.effect_time_left.saturating_sub(tick);
+
+        if self.stacks.effect_time_left > Duration::ZERO {
+            return false;
+        }
+
+        self.stacks.set_current_stacks(0);
+        self.abilities_hit.clear();
+        self.set_stack_stats(stats, level)
+    }
+
+    fn add_stacks(&mut self, stacks: i32) {
+        self.stacks.set_current_stacks((self.stacks.current_stacks + stacks).min(self.stacks.max_stacks));
+        self.stacks.set_effect_time_left(self.keystone.stack_duration());
+    }
+
+    /// Replace the stats granted by the previous number of stacks with those for the current number.
+    /// Returns true if anything changed.
+    fn set_stack_stats(&mut self, stats: &mut ChampStats, level: i32) -> bool {
+        let stacks = self.stacks.current_stacks as f32;
+        let (mut ad, mut ap, mut as_) = (0.0, 0.0, 0.0);
+        let mut uncapped_changed = false;
+
+        match self.keystone {
+            Keystone::Conqueror => {
+                let adaptive_force = stacks * by_level(CONQUEROR_ADAPTIVE_PER_STACK, level);
+
+                if stats.ap - self.bonus_ap > stats.bonus_ad - self.bonus_ad {
+                    ap = adaptive_force;
+                } else {
+                    ad = adaptive_force * ADAPTIVE_FORCE_AD_RATIO;
+                }
+            }
+            Keystone::LethalTempo => {
+                let per_stack = if stats.is_melee() { LETHAL_TEMPO_MELEE_AS_PER_STACK } else { LETHAL_TEMPO_RANGED_AS_PER_STACK };
+                as_ = stacks * by_level(per_stack, level);
+
+                let uncapped = self.stacks.current_stacks == self.stacks.max_stacks;
+                uncapped_changed = uncapped != stats.attack_speed_uncapped;
+                stats.attack_speed_uncapped = uncapped;
+            }
+            _ => {}
+        }
+
+        let changed = uncapped_changed || ad != self.bonus_ad || ap != self.bonus_ap || as_ != self.bonus_as;
+
+        stats.bonus_ad += ad - self.bonus_ad;
+        stats.ap += ap - self.bonus_ap;
+        stats.bonus_as += as_ - self.bonus_as;
+
+        self.bonus_ad = ad;
+        self.bonus_ap = ap;
+        self.bonus_as = as_;
+
+        changed
+    }
+}
+
+/// Linear scaling from a value at level 1 to a value at level 18.
+fn by_level((min, max): (f32, f32), level: i32) -> f32 {
+    let level = level.clamp(1, 18);
+
+    min + (max - min) * (level - 1) as f32 / 17.0
+}
+
+/// Adaptive damage is magical for champions with more AP than bonus AD, and physical otherwise.
+fn adaptive_damage(stats: &ChampStats, damage: f32) -> Damage {
+    if stats.ap > stats.bonus_ad {
+        Damage::new(0.0, damage, 0.0)
+    } else {
+        Damage::new(damage, 0.0, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::constants::TICKS_PER_SECOND;
+    use crate::ability::AbilitySlot;
+    use crate::keystone::{by_level, HitSource, Keystone, KeystoneState};
+    use crate::utils::create_champion_by_name;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Keystone::from_name("Press the Attack"), Some(Keystone::PressTheAttack));
+        assert_eq!(Keystone::from_name("grasp of the undying"), Some(Keystone::GraspOfTheUndying));
+        assert_eq!(Keystone::from_name("Aftershock"), None);
+    }
+
+    #[test]
+    fn test_by_level() {
+        assert_eq!(by_level((40.0, 160.0), 1), 40.0);
+        assert_eq!(by_level((40.0, 160.0), 18), 160.0);
+    }
+
+    #[test]
+    fn test_conqueror_stacks() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut state = KeystoneState::new(Keystone::Conqueror);
+
+        for _ in 0..7 {
+            state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Attack);
+        }
+
+        // Melee attacks grant 2 stacks, up to 12
+        assert_eq!(state.stacks.current_stacks, 12);
+        assert_eq!(champion.champ_stats.bonus_ad, 12.0 * 1.8 * 0.6);
+        assert_eq!(state.healing_from_damage(&champion.champ_stats, 100.0), 8.0);
+
+        // Stacks fall off after 5 seconds without a hit
+        for _ in 0..(5.0 * TICKS_PER_SECOND) as i32 + 1 {
+            state.tick(&mut champion.champ_stats, 1);
+        }
+
+        assert_eq!(state.stacks.current_stacks, 0);
+        assert_eq!(champion.champ_stats.bonus_ad, 0.0);
+        assert_eq!(state.healing_from_damage(&champion.champ_stats, 100.0), 0.0);
+    }
+
+    #[test]
+    fn test_lethal_tempo_stacks() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut state = KeystoneState::new(Keystone::LethalTempo);
+
+        for _ in 0..6 {
+            assert_eq!(state.on_hit(&mut champion.champ_stats, 18, 685.0, HitSource::Attack).damage.total(), 0.0);
+        }
+
+        assert_eq!(champion.champ_stats.bonus_as, 6.0 * 0.12);
+
+        // Attacks at max stacks deal bonus damage
+        assert_eq!(state.on_hit(&mut champion.champ_stats, 18, 685.0, HitSource::Attack).damage.physical_component, 24.0);
+    }
+
+    #[test]
+    fn test_lethal_tempo_lifts_attack_speed_cap() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut state = KeystoneState::new(Keystone::LethalTempo);
+
+        champion.set_level(18);
+        champion.champ_stats.bonus_as += 3.0;
+        champion.champ_stats.calculate_as_from_level(18);
+        assert_eq!(champion.champ_stats.as_, 2.5);
+
+        for _ in 0..6 {
+            state.on_hit(&mut champion.champ_stats, 18, 685.0, HitSource::Attack);
+            champion.champ_stats.calculate_as_from_level(18);
+        }
+
+        assert!(champion.champ_stats.attack_speed_uncapped);
+        assert!(champion.champ_stats.as_ > 2.5);
+
+        // The cap is back once the stacks fall off
+        for _ in 0..(6.0 * TICKS_PER_SECOND) as i32 + 1 {
+            if state.tick(&mut champion.champ_stats, 18) {
+                champion.champ_stats.calculate_as_from_level(18);
+            }
+        }
+
+        assert!(!champion.champ_stats.attack_speed_uncapped);
+        assert_eq!(champion.champ_stats.as_, 2.5);
+    }
+
+    #[test]
+    fn test_press_the_attack() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut state = KeystoneState::new(Keystone::PressTheAttack);
+
+        assert_eq!(state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Attack).damage.total(), 0.0);
+        assert_eq!(state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Attack).damage.total(), 0.0);
+        assert_eq!(state.damage_amp(), 1.0);
+        assert_eq!(state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Attack).damage.physical_component, 40.0);
+        assert_eq!(state.damage_amp(), 1.08);
+
+        // No stacks while the target is exposed
+        state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Attack);
+        assert_eq!(state.stacks.current_stacks, 0);
+    }
+
+    #[test]
+    fn test_electrocute_window_and_cooldown() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut state = KeystoneState::new(Keystone::Electrocute);
+
+        state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Attack);
+        state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Attack);
+
+        // The third hit comes too late
+        for _ in 0..(3.0 * TICKS_PER_SECOND) as i32 + 1 {
+            state.tick(&mut champion.champ_stats, 1);
+        }
+
+        assert_eq!(state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Attack).damage.total(), 0.0);
+        state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Attack);
+        assert_eq!(state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Attack).damage.physical_component, 30.0);
+        assert_eq!(state.cooldown_left, Duration::from_secs(25));
+
+        // On cooldown
+        for _ in 0..3 {
+            assert_eq!(state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Attack).damage.total(), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_grasp_charges_in_combat() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut state = KeystoneState::new(Keystone::GraspOfTheUndying);
+
+        assert_eq!(state.on_hit(&mut champion.champ_stats, 1, 1000.0, HitSource::Attack).damage.total(), 0.0);
+
+        for _ in 0..(4.0 * TICKS_PER_SECOND) as i32 + 1 {
+            state.tick(&mut champion.champ_stats, 1);
+        }
+
+        let hit = state.on_hit(&mut champion.champ_stats, 1, 1000.0, HitSource::Attack);
+
+        assert_eq!(hit.damage.magical_component, 35.0);
+        assert_eq!(hit.healing, 13.0);
+        assert_eq!(champion.champ_stats.bonus_health, 5.0);
+        assert_eq!(state.on_hit(&mut champion.champ_stats, 1, 1000.0, HitSource::Attack).damage.total(), 0.0);
+    }
+
+    #[test]
+    fn test_electrocute_counts_each_ability_once() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut state = KeystoneState::new(Keystone::Electrocute);
+
+        // A second hit from the same ability in the window does not count
+        state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Ability(AbilitySlot::Q));
+        assert_eq!(state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Ability(AbilitySlot::Q)).damage.total(), 0.0);
+        assert_eq!(state.stacks.current_stacks, 1);
+
+        state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Ability(AbilitySlot::W));
+        assert_eq!(state.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Attack).damage.physical_component, 30.0);
+    }
+
+    #[test]
+    fn test_attack_keystones_ignore_abilities() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut conqueror = KeystoneState::new(Keystone::Conqueror);
+        let mut press_the_attack = KeystoneState::new(Keystone::PressTheAttack);
+
+        conqueror.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Ability(AbilitySlot::Q));
+        press_the_attack.on_hit(&mut champion.champ_stats, 1, 685.0, HitSource::Ability(AbilitySlot::Q));
+
+        assert_eq!(conqueror.stacks.current_stacks, 2);
+        assert_eq!(press_the_attack.stacks.current_stacks, 0);
+    }
+}
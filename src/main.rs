@@ -1,19 +1,46 @@
+use std::process::ExitCode;
 use std::time::Duration;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 use crate::build::Build;
 use crate::scenario::Scenario;
 
+mod ability;
 mod item;
+mod keystone;
+mod rotation;
+mod rune;
 mod build;
 mod scenario;
 mod effects;
+mod shield;
 mod simulation;
 mod champion;
+mod crit;
 mod damage;
 mod utils;
 mod constants;
+mod data;
+mod cli;
 
-fn main() {
-    let mut champion = utils::create_champion_by_name("aatrox");
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if !args.is_empty() {
+        return cli::run(&args);
+    }
+
+    match run_example() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_example() -> Result<(), data::DataError> {
+    let mut champion = utils::create_champion_by_name("aatrox")?;
     let test_on_hit_effect_phys = effects::LimitedUseOnHitEffect::new("test", 10.0, effects::DamageType::Physical, 1, Duration::from_secs(10), true);
     let test_on_hit_effect_magic = effects::LimitedUseOnHitEffect::new("test2", 20.0, effects::DamageType::Magical, 1, Duration::from_secs(10), true);
     let test_on_hit_effect_true = effects::LimitedUseOnHitEffect::new("test3", 30.0, effects::DamageType::True, 1, Duration::from_secs(10), true);
@@ -23,22 +50,27 @@ fn main() {
     champion.add_friendly_limited_use_on_hit_effect(test_on_hit_effect_true);
     champion.set_level(1);
 
-    let mut dummy = utils::create_champion_by_name("dummy");
+    let mut dummy = utils::create_champion_by_name("dummy")?;
 
     println!("Champion: {}, {}", champion.name, champion.champ_stats.health);
 
-    dummy.take_auto_attack_damage(&mut champion);
-    dummy.take_auto_attack_damage(&mut champion);
+    // The attacks on the dummy and the scenario follow one seed, which the scenario prints
+    let seed = rand::thread_rng().gen();
+    let mut rng = StdRng::seed_from_u64(seed);
 
-    let mut champion1 = utils::create_champion_by_name("aatrox");
+    dummy.take_auto_attack_damage(&mut champion, &mut rng);
+    dummy.take_auto_attack_damage(&mut champion, &mut rng);
+
+    let mut champion1 = utils::create_champion_by_name("aatrox")?;
     champion1.set_level(6);
     let champ1_build = Build::new(&champion1, vec![]);
 
-    let champion2 = utils::create_champion_by_name("aatrox");
+    let champion2 = utils::create_champion_by_name("aatrox")?;
     let champ2_build = Build::new(&champion2, vec![]);
 
-    let mut scenario = Scenario::new(0, Duration::from_secs_f32(0.5), champ1_build, champ2_build);
+    let mut scenario = Scenario::new(0, champ1_build, champ2_build);
+    scenario.seed = Some(seed);
     scenario.calculate_scenario();
-}
 
-// TODO: Runes!
+    Ok(())
+}
@@ -0,0 +1,658 @@
+use std::fmt;
+use crate::ability::{Ability, AbilitySlot};
+use crate::champion::Champion;
+use crate::constants::{DEFAULT_REACTION_DELAY, TICKS_PER_SECOND};
+use crate::effects::CrowdControlType;
+
+// Of the chance to miss a skillshot, the part that is lost against a slowed target
+const SLOWED_MISS_REDUCTION: f32 = 0.5;
+const MISTIMED_ATTACK_DELAY: f32 = 0.25; // In seconds; how late a mistimed attack starts
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    AutoAttack,
+    Cast(AbilitySlot),
+}
+
+/// When a priority rule may be used. Health thresholds are fractions of max health.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    Always,
+    TargetHealthBelow(f32),
+    TargetHealthAbove(f32),
+    OwnHealthBelow(f32),
+    OwnHealthAbove(f32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriorityRule {
+    pub(crate) action: Action,
+    pub(crate) condition: Condition,
+}
+
+/// What a champion does during a fight.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rotation {
+    /// Actions done in order, each waiting until it is available. Actions that can never become
+    /// available (abilities that are not learned, and auto attacks for champions that cannot attack)
+    /// are skipped, and once the combo is done the champion only auto attacks.
+    Combo(Vec<Action>),
+    /// Whenever the champion can act, it does the first action whose condition holds and that is
+    /// available.
+    Priority(Vec<PriorityRule>),
+}
+
+/// How well one side lands skillshots. The default is an average player, using each ability's hit
+/// chance as is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Accuracy {
+    /// Every ability lands.
+    Perfect,
+    /// Multiplies the hit chance of each skillshot; 1.0 is an average player.
+    Skill(f32),
+}
+
+impl Default for Accuracy {
+    fn default() -> Self {
+        Accuracy::Skill(1.0)
+    }
+}
+
+/// How cleanly one side plays out its rotation. The default is perfect mechanics; only the reaction
+/// to being engaged on takes time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExecutionProfile {
+    pub(crate) reaction_delay: f32, // In seconds; when the side does not act first, it starts this late
+    pub(crate) windup_delay: f32, // In seconds; lost after every attack windup, e.g. to slow attack-moves
+    pub(crate) cancels_backswing: bool, // Abilities can be cast as soon as an attack's windup is over
+    pub(crate) mistimed_attack_chance: f32, // Chance that the next attack starts MISTIMED_ATTACK_DELAY late
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseRotationError {
+    pub(crate) text: String,
+    pub(crate) message: String,
+}
+
+/// One side's progress through its rotation during a fight.
+#[derive(Debug, Clone)]
+pub struct RotationState {
+    pub(crate) rotation: Rotation,
+    pub(crate) next_step: usize, // Index of the next combo action
+    pub(crate) busy_until_tick: i32, // Casting or winding up an attack; nothing else can be done until then
+    pub(crate) next_attack_time: f32, // In ticks; kept fractional so attack timings do not drift
+    pub(crate) attack_lands_tick: Option<i32>, // The tick the attack that is winding up deals its damage
+    pub(crate) execution: ExecutionProfile,
+    pub(crate) skipped: Vec<Action>, // Combo actions skipped since `take_skipped` was last called
+}
+
+impl Action {
+    fn parse(text: &str) -> Result<Action, ParseRotationError> {
+        match text.trim().to_lowercase().as_str() {
+            "aa" | "auto" => Ok(Action::AutoAttack),
+            "passive" | "p" => Err(ParseRotationError::new(text, "passives cannot be cast")),
+            name => AbilitySlot::from_name(name)
+                .map(Action::Cast)
+                .ok_or_else(|| ParseRotationError::new(text, "expected AA, Q, W, E or R")),
+        }
+    }
+}
+
+impl Condition {
+    /// Parse a condition such as `target < 40%` or `self > 50%`.
+    fn parse(text: &str) -> Result<Condition, ParseRotationError> {
+        let parts: Vec<&str> = text.split_whitespace().collect();
+        let error = || ParseRotationError::new(text, "expected a condition like 'target < 40%' or 'self > 50%'");
+
+        let [subject, operator, value] = parts.as_slice() else {
+            return Err(error());
+        };
+
+        let percent = value.strip_suffix('%')
+            .and_then(|value| value.parse::<f32>().ok())
+            .filter(|value| (0.0..=100.0).contains(value))
+            .ok_or_else(error)?;
+        let fraction = percent / 100.0;
+
+        match (subject.to_lowercase().as_str(), *operator) {
+            ("target", "<") => Ok(Condition::TargetHealthBelow(fraction)),
+            ("target", ">") => Ok(Condition::TargetHealthAbove(fraction)),
+            ("self", "<") => Ok(Condition::OwnHealthBelow(fraction)),
+            ("self", ">") => Ok(Condition::OwnHealthAbove(fraction)),
+            _ => Err(error()),
+        }
+    }
+
+    fn holds(&self, champion: &Champion, target: &Champion) -> bool {
+        let health_fraction = |champion: &Champion| champion.champ_stats.health_percent();
+
+        match *self {
+            Condition::Always => true,
+            Condition::TargetHealthBelow(fraction) => health_fraction(target) < fraction,
+            Condition::TargetHealthAbove(fraction) => health_fraction(target) > fraction,
+            Condition::OwnHealthBelow(fraction) => health_fraction(champion) < fraction,
+            Condition::OwnHealthAbove(fraction) => health_fraction(champion) > fraction,
+        }
+    }
+}
+
+impl Rotation {
+    /// Parse a combo of space separated actions, e.g. `Q AA W E Q R AA`.
+    pub fn parse_combo(text: &str) -> Result<Rotation, ParseRotationError> {
+        let actions = text.split_whitespace()
+            .map(Action::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if actions.is_empty() {
+            return Err(ParseRotationError::new(text, "a combo needs at least one action"));
+        }
+
+        Ok(Rotation::Combo(actions))
+    }
+
+    /// Parse a comma separated priority list, e.g. `R if target < 40%, Q, AA`.
+    pub fn parse_priority(text: &str) -> Result<Rotation, ParseRotationError> {
+        let rules = text.split(',')
+            .map(|rule| match rule.split_once(" if ") {
+                Some((action, condition)) => Ok(PriorityRule {
+                    action: Action::parse(action)?,
+                    condition: Condition::parse(condition)?,
+                }),
+                None => Ok(PriorityRule { action: Action::parse(rule)?, condition: Condition::Always }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Rotation::Priority(rules))
+    }
+}
+
+/// Cast every ability as soon as it is ready, and auto attack otherwise.
+impl Default for Rotation {
+    fn default() -> Rotation {
+        let actions = [
+            Action::Cast(AbilitySlot::Q),
+            Action::Cast(AbilitySlot::W),
+            Action::Cast(AbilitySlot::E),
+            Action::Cast(AbilitySlot::R),
+            Action::AutoAttack,
+        ];
+
+        Rotation::Priority(actions.into_iter().map(|action| PriorityRule { action, condition: Condition::Always }).collect())
+    }
+}
+
+impl Accuracy {
+    /// Parse `perfect`, `average` or a skill multiplier such as `0.8`.
+    pub fn parse(text: &str) -> Option<Accuracy> {
+        match text.trim().to_lowercase().as_str() {
+            "perfect" => Some(Accuracy::Perfect),
+            "average" => Some(Accuracy::Skill(1.0)),
+            skill => skill.parse::<f32>().ok().filter(|skill| *skill >= 0.0 && skill.is_finite()).map(Accuracy::Skill),
+        }
+    }
+
+    /// The chance to land `ability` on `target`. Immobilized targets cannot dodge anything, and
+    /// slowed targets are easier to hit.
+    pub fn hit_chance(&self, ability: &Ability, target: &Champion) -> f32 {
+        let hit_chance = match (self, ability.hit_chance) {
+            (Accuracy::Skill(skill), Some(hit_chance)) => (hit_chance * skill).clamp(0.0, 1.0),
+            _ => return 1.0,
+        };
+
+        if target.is_immobilized() {
+            1.0
+        } else if target.is_crowd_controlled(CrowdControlType::Slow) {
+            hit_chance + (1.0 - hit_chance) * SLOWED_MISS_REDUCTION
+        } else {
+            hit_chance
+        }
+    }
+}
+
+impl ExecutionProfile {
+    /// An average player: slower to react, loses time kiting between attacks, lets every attack
+    /// finish before casting and now and then starts an attack late.
+    pub fn realistic() -> ExecutionProfile {
+        ExecutionProfile {
+            reaction_delay: 0.75,
+            windup_delay: 0.1,
+            cancels_backswing: false,
+            mistimed_attack_chance: 0.2,
+        }
+    }
+
+    /// Parse `perfect` or `realistic`.
+    pub fn parse(text: &str) -> Option<ExecutionProfile> {
+        match text.trim().to_lowercase().as_str() {
+            "perfect" => Some(ExecutionProfile::default()),
+            "realistic" => Some(ExecutionProfile::realistic()),
+            _ => None,
+        }
+    }
+
+    /// The tick a side that reacts to the other one can act first.
+    pub fn reaction_ticks(&self) -> i32 {
+        (self.reaction_delay * TICKS_PER_SECOND).round() as i32
+    }
+}
+
+impl Default for ExecutionProfile {
+    fn default() -> ExecutionProfile {
+        ExecutionProfile {
+            reaction_delay: DEFAULT_REACTION_DELAY,
+            windup_delay: 0.0,
+            cancels_backswing: true,
+            mistimed_attack_chance: 0.0,
+        }
+    }
+}
+
+impl RotationState {
+    /// Start a rotation. A champion that reacts to the other one cannot act before `first_tick`.
+    pub fn new(rotation: Rotation, first_tick: i32) -> RotationState {
+        RotationState {
+            rotation,
+            next_step: 0,
+            busy_until_tick: first_tick,
+            next_attack_time: first_tick as f32,
+            attack_lands_tick: None,
+            execution: ExecutionProfile::default(),
+            skipped: Vec::new(),
+        }
+    }
+
+    pub fn with_execution(mut self, execution: ExecutionProfile) -> RotationState {
+        self.execution = execution;
+        self
+    }
+
+    /// The action `champion` takes this tick, if any. `can_attack` is false for champions that
+    /// never attack.
+    pub fn next_action(&mut self, tick: i32, champion: &Champion, target: &Champion, can_attack: bool) -> Option<Action> {
+        if tick < self.busy_until_tick || !champion.can_act() {
+            return None;
+        }
+
+        let attack_ready = can_attack && tick as f32 >= self.next_attack_time;
+        let available = |action: &Action| match action {
+            Action::AutoAttack => attack_ready,
+            Action::Cast(slot) => champion.can_cast(*slot),
+        };
+
+        match &self.rotation {
+            Rotation::Combo(actions) => {
+                while let Some(action) = actions.get(self.next_step) {
+                    let never_available = match action {
+                        Action::AutoAttack => !can_attack,
+                        Action::Cast(slot) => !champion.has_learned(*slot),
+                    };
+
+                    if never_available {
+                        self.skipped.push(*action);
+                        self.next_step += 1;
+                        continue;
+                    }
+
+                    if !available(action) {
+                        return None;
+                    }
+
+                    self.next_step += 1;
+                    return Some(*action);
+                }
+
+                if attack_ready { Some(Action::AutoAttack) } else { None }
+            }
+            Rotation::Priority(rules) => rules.iter()
+                .find(|rule| rule.condition.holds(champion, target) && available(&rule.action))
+                .map(|rule| rule.action),
+        }
+    }
+
+    /// The combo actions skipped because they could never be done, since the last call.
+    pub fn take_skipped(&mut self) -> Vec<Action> {
+        std::mem::take(&mut self.skipped)
+    }
+
+    /// Start an auto attack this tick, which takes `attack_time` ticks until the next one and deals
+    /// its damage after `windup` ticks. An attack made as soon as it was ready is timed from when it
+    /// became ready, so the rounding to ticks does not add up over the fight.
+    ///
+    /// The execution profile's windup delay pushes back both the next action and the next attack,
+    /// and without backswing cancels nothing else can be done until the attack is over.
+    pub fn attacked(&mut self, tick: i32, attack_time: f32, windup: f32) {
+        let start = if tick as f32 - self.next_attack_time < 1.0 { self.next_attack_time } else { tick as f32 };
+        let lands = tick + windup.round() as i32;
+        let windup_delay = self.execution.windup_delay * TICKS_PER_SECOND;
+        let busy_until = if self.execution.cancels_backswing {
+            lands + windup_delay.round() as i32
+        } else {
+            (start + attack_time + windup_delay).round() as i32
+        };
+
+        self.next_attack_time = start + attack_time + windup_delay;
+        self.busy_until_tick = self.busy_until_tick.max(busy_until);
+        self.attack_lands_tick = Some(lands);
+    }
+
+    /// Start the next attack late, for an attack the player did not click in time.
+    pub fn mistime_attack(&mut self) {
+        self.next_attack_time += MISTIMED_ATTACK_DELAY * TICKS_PER_SECOND;
+    }
+
+    /// Whether the attack that is winding up deals its damage this tick.
+    pub fn attack_lands(&mut self, tick: i32) -> bool {
+        match self.attack_lands_tick {
+            Some(lands) if lands <= tick => {
+                self.attack_lands_tick = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Cancel the attack that is winding up, e.g. because the champion got stunned. The champion can
+    /// attack again as soon as it can act.
+    pub fn cancel_attack(&mut self, tick: i32) {
+        if self.attack_lands_tick.take().is_some() {
+            self.next_attack_time = tick as f32;
+        }
+    }
+
+    /// Reset the attack timer, so the next attack can start as soon as the current cast is done.
+    pub fn reset_attack(&mut self) {
+        self.next_attack_time = self.next_attack_time.min(self.busy_until_tick as f32);
+    }
+
+    /// Lock the champion for the cast time of an ability cast this tick.
+    pub fn cast(&mut self, tick: i32, cast_time: f32) {
+        self.busy_until_tick = tick + (cast_time * TICKS_PER_SECOND).round() as i32;
+    }
+}
+
+impl ParseRotationError {
+    fn new(text: &str, message: &str) -> ParseRotationError {
+        ParseRotationError {
+            text: text.trim().to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::AutoAttack => write!(f, "AA"),
+            Action::Cast(slot) => write!(f, "{}", slot),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Always => Ok(()),
+            Condition::TargetHealthBelow(fraction) => write!(f, " if target < {}%", fraction * 100.0),
+            Condition::TargetHealthAbove(fraction) => write!(f, " if target > {}%", fraction * 100.0),
+            Condition::OwnHealthBelow(fraction) => write!(f, " if self < {}%", fraction * 100.0),
+            Condition::OwnHealthAbove(fraction) => write!(f, " if self > {}%", fraction * 100.0),
+        }
+    }
+}
+
+/// Formatted the same way as it is parsed.
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = match self {
+            Rotation::Combo(actions) => actions.iter().map(|action| action.to_string()).collect(),
+            Rotation::Priority(rules) => rules.iter().map(|rule| format!("{}{}", rule.action, rule.condition)).collect(),
+        };
+
+        match self {
+            Rotation::Combo(_) => write!(f, "{}", parts.join(" ")),
+            Rotation::Priority(_) => write!(f, "{}", parts.join(", ")),
+        }
+    }
+}
+
+impl fmt::Display for Accuracy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Accuracy::Perfect => write!(f, "perfect"),
+            Accuracy::Skill(skill) => write!(f, "{}x average", skill),
+        }
+    }
+}
+
+impl fmt::Display for ExecutionProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if *self == ExecutionProfile::default() {
+            return write!(f, "perfect");
+        }
+
+        if *self == ExecutionProfile::realistic() {
+            return write!(f, "realistic");
+        }
+
+        let backswing = if self.cancels_backswing { "backswing cancels" } else { "no backswing cancels" };
+
+        write!(f, "{}s reaction, {}s lost after windups, {}, {}% mistimed attacks", self.reaction_delay, self.windup_delay, backswing, self.mistimed_attack_chance * 100.0)
+    }
+}
+
+impl fmt::Display for ParseRotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid rotation '{}': {}", self.text, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ability::AbilitySlot;
+    use crate::effects::{CrowdControl, CrowdControlType};
+    use crate::rotation::{Accuracy, Action, Condition, ExecutionProfile, Rotation, RotationState};
+    use crate::utils::create_champion_by_name;
+
+    #[test]
+    fn test_parse_combo() {
+        let rotation = Rotation::parse_combo("Q AA w e").unwrap();
+
+        assert_eq!(rotation, Rotation::Combo(vec![
+            Action::Cast(AbilitySlot::Q),
+            Action::AutoAttack,
+            Action::Cast(AbilitySlot::W),
+            Action::Cast(AbilitySlot::E),
+        ]));
+        assert_eq!(rotation.to_string(), "Q AA W E");
+        assert!(Rotation::parse_combo("Q X").is_err());
+        assert!(Rotation::parse_combo("passive").is_err());
+        assert!(Rotation::parse_combo(" ").is_err());
+    }
+
+    #[test]
+    fn test_parse_priority() {
+        let rotation = Rotation::parse_priority("R if target < 40%, Q, AA").unwrap();
+
+        match &rotation {
+            Rotation::Priority(rules) => {
+                assert_eq!(rules.len(), 3);
+                assert_eq!(rules[0].action, Action::Cast(AbilitySlot::R));
+                assert_eq!(rules[0].condition, Condition::TargetHealthBelow(0.4));
+                assert_eq!(rules[2].condition, Condition::Always);
+            }
+            Rotation::Combo(_) => panic!("expected a priority list"),
+        }
+
+        assert_eq!(rotation.to_string(), "R if target < 40%, Q, AA");
+        assert!(Rotation::parse_priority("R if target 40%").is_err());
+        assert!(Rotation::parse_priority("R if enemy < 40%").is_err());
+        assert!(Rotation::parse_priority("R if target < 140%").is_err());
+    }
+
+    #[test]
+    fn test_combo_waits_and_skips_unlearned() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let target = create_champion_by_name("test-bruiser").unwrap();
+        champion.set_ability_rank(AbilitySlot::Q, 1);
+
+        let mut state = RotationState::new(Rotation::parse_combo("W Q AA Q").unwrap(), 0);
+
+        // W is not learned, so the combo starts with Q
+        assert_eq!(state.next_action(0, &champion, &target, true), Some(Action::Cast(AbilitySlot::Q)));
+        assert_eq!(state.take_skipped(), vec![Action::Cast(AbilitySlot::W)]);
+        state.cast(0, 0.25);
+
+        assert_eq!(state.next_action(5, &champion, &target, true), None);
+        assert_eq!(state.next_action(8, &champion, &target, true), Some(Action::AutoAttack));
+        state.attacked(8, 46.0, 0.0);
+
+        // The rotation does not cast anything itself, so Q is still ready
+        assert_eq!(state.next_action(9, &champion, &target, true), Some(Action::Cast(AbilitySlot::Q)));
+        assert_eq!(state.next_action(10, &champion, &target, true), None);
+        assert_eq!(state.next_action(54, &champion, &target, true), Some(Action::AutoAttack));
+    }
+
+    #[test]
+    fn test_combo_skips_attacks_for_champions_that_cannot_attack() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let target = create_champion_by_name("test-bruiser").unwrap();
+        champion.set_ability_rank(AbilitySlot::Q, 1);
+
+        let mut state = RotationState::new(Rotation::parse_combo("AA Q AA").unwrap(), 0);
+
+        // Waiting for the attack would block the combo forever
+        assert_eq!(state.next_action(0, &champion, &target, false), Some(Action::Cast(AbilitySlot::Q)));
+        assert_eq!(state.take_skipped(), vec![Action::AutoAttack]);
+
+        assert_eq!(state.next_action(30, &champion, &target, false), None);
+        assert_eq!(state.take_skipped(), vec![Action::AutoAttack]);
+        assert!(state.take_skipped().is_empty());
+    }
+
+    #[test]
+    fn test_attack_windup_and_timer() {
+        let champion = create_champion_by_name("test-bruiser").unwrap();
+        let target = create_champion_by_name("test-bruiser").unwrap();
+        let mut state = RotationState::new(Rotation::default(), 0);
+
+        assert_eq!(state.next_action(0, &champion, &target, true), Some(Action::AutoAttack));
+        state.attacked(0, 20.4, 5.0);
+
+        // The damage lands after the windup, and nothing else can be done until then
+        assert!(!state.attack_lands(4));
+        assert_eq!(state.next_action(4, &champion, &target, true), None);
+        assert!(state.attack_lands(5));
+        assert!(!state.attack_lands(6));
+
+        // Attacks made as soon as they are ready keep the fractional attack time
+        assert_eq!(state.next_action(20, &champion, &target, true), None);
+        assert_eq!(state.next_action(21, &champion, &target, true), Some(Action::AutoAttack));
+        state.attacked(21, 20.4, 5.0);
+        assert_eq!(state.next_attack_time, 40.8);
+        assert_eq!(state.next_action(41, &champion, &target, true), Some(Action::AutoAttack));
+    }
+
+    #[test]
+    fn test_attack_reset_and_cancel() {
+        let champion = create_champion_by_name("test-bruiser").unwrap();
+        let target = create_champion_by_name("test-bruiser").unwrap();
+        let mut state = RotationState::new(Rotation::default(), 0);
+
+        state.attacked(0, 30.0, 6.0);
+        assert!(state.attack_lands(6));
+
+        // A cast that resets the attack timer lets the champion attack right after the cast time
+        state.cast(10, 0.25);
+        state.reset_attack();
+        assert_eq!(state.next_action(17, &champion, &target, true), None);
+        assert_eq!(state.next_action(18, &champion, &target, true), Some(Action::AutoAttack));
+
+        state.attacked(18, 30.0, 6.0);
+        state.cancel_attack(20);
+        assert!(!state.attack_lands(24));
+        assert_eq!(state.next_attack_time, 20.0);
+    }
+
+    #[test]
+    fn test_execution_profile() {
+        let champion = create_champion_by_name("test-bruiser").unwrap();
+        let target = create_champion_by_name("test-bruiser").unwrap();
+        let mut state = RotationState::new(Rotation::default(), 0).with_execution(ExecutionProfile::realistic());
+
+        // Without backswing cancels the whole attack plays out, and the windup delay comes on top
+        state.attacked(0, 30.0, 6.0);
+        assert!(state.attack_lands(6));
+        assert_eq!(state.busy_until_tick, 33);
+        assert_eq!(state.next_action(32, &champion, &target, true), None);
+        assert_eq!(state.next_action(33, &champion, &target, true), Some(Action::AutoAttack));
+
+        state.mistime_attack();
+        assert_eq!(state.next_attack_time, 40.5);
+
+        assert_eq!(ExecutionProfile::parse("Perfect"), Some(ExecutionProfile::default()));
+        assert_eq!(ExecutionProfile::parse("realistic").unwrap().to_string(), "realistic");
+        assert_eq!(ExecutionProfile::parse("sloppy"), None);
+        assert_eq!(ExecutionProfile::realistic().reaction_ticks(), 23);
+    }
+
+    #[test]
+    fn test_priority_conditions() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut target = create_champion_by_name("test-bruiser").unwrap();
+        champion.set_ability_rank(AbilitySlot::R, 1);
+
+        let mut state = RotationState::new(Rotation::parse_priority("R if target < 40%, AA").unwrap(), 0);
+
+        assert_eq!(state.next_action(0, &champion, &target, true), Some(Action::AutoAttack));
+        assert_eq!(state.next_action(0, &champion, &target, false), None);
+
+        target.champ_stats.health = 100.0;
+
+        assert_eq!(state.next_action(0, &champion, &target, true), Some(Action::Cast(AbilitySlot::R)));
+    }
+
+    #[test]
+    fn test_disabled_champion_does_not_act() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+        let target = create_champion_by_name("test-bruiser").unwrap();
+        let mut state = RotationState::new(Rotation::default(), 0);
+
+        champion.apply_crowd_control(CrowdControl { kind: CrowdControlType::Root, duration: 1.0 });
+        assert_eq!(state.next_action(0, &champion, &target, true), Some(Action::AutoAttack));
+
+        champion.apply_crowd_control(CrowdControl { kind: CrowdControlType::Stun, duration: 1.0 });
+        assert_eq!(state.next_action(0, &champion, &target, true), None);
+    }
+
+    #[test]
+    fn test_hit_chance() {
+        let champion = create_champion_by_name("test-bruiser").unwrap();
+        let mut target = create_champion_by_name("test-bruiser").unwrap();
+        let mut ability = champion.abilities[&AbilitySlot::Q].ability.clone();
+
+        // Abilities without a hit chance always land
+        assert_eq!(Accuracy::Skill(0.5).hit_chance(&ability, &target), 1.0);
+
+        ability.hit_chance = Some(0.6);
+
+        assert_eq!(Accuracy::Perfect.hit_chance(&ability, &target), 1.0);
+        assert_eq!(Accuracy::Skill(1.0).hit_chance(&ability, &target), 0.6);
+        assert_eq!(Accuracy::Skill(2.0).hit_chance(&ability, &target), 1.0);
+
+        target.apply_crowd_control(CrowdControl { kind: CrowdControlType::Slow, duration: 1.0 });
+        assert_eq!(Accuracy::Skill(1.0).hit_chance(&ability, &target), 0.8);
+
+        target.apply_crowd_control(CrowdControl { kind: CrowdControlType::Root, duration: 1.0 });
+        assert_eq!(Accuracy::Skill(1.0).hit_chance(&ability, &target), 1.0);
+    }
+
+    #[test]
+    fn test_parse_accuracy() {
+        assert_eq!(Accuracy::default(), Accuracy::Skill(1.0));
+        assert_eq!(Accuracy::parse("perfect"), Some(Accuracy::Perfect));
+        assert_eq!(Accuracy::parse("Average"), Some(Accuracy::Skill(1.0)));
+        assert_eq!(Accuracy::parse("0.8"), Some(Accuracy::Skill(0.8)));
+        assert_eq!(Accuracy::parse("-1"), None);
+        assert_eq!(Accuracy::parse("great"), None);
+    }
+}
@@ -0,0 +1,170 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::champion::stats::ChampStats;
+
+pub(crate) const KEYSTONE_SLOT: u8 = 0;
+pub(crate) const MINOR_SLOTS: [u8; 3] = [1, 2, 3];
+
+// Stat shard values
+const ADAPTIVE_FORCE: f32 = 9.0;
+pub(crate) const ADAPTIVE_FORCE_AD_RATIO: f32 = 0.6; // 1 adaptive force is 0.6 AD or 1 AP
+const SHARD_ATTACK_SPEED: f32 = 0.1;
+const SHARD_ABILITY_HASTE: f32 = 8.0;
+const SHARD_ARMOR: f32 = 6.0;
+const SHARD_MR: f32 = 8.0;
+const SHARD_HEALTH_MIN: f32 = 15.0; // At level 1
+const SHARD_HEALTH_MAX: f32 = 140.0; // At level 18
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuneTree {
+    Precision,
+    Domination,
+    Sorcery,
+    Resolve,
+    Inspiration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rune {
+    pub(crate) id: i32,
+    pub(crate) name: String,
+    pub(crate) tree: RuneTree,
+    pub(crate) slot: u8, // 0 is the keystone row
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatShard {
+    AdaptiveForce,
+    AttackSpeed,
+    AbilityHaste,
+    Armor,
+    MagicResist,
+    ScalingHealth,
+}
+
+/// The shards allowed in each of the three shard rows.
+pub(crate) const SHARD_ROWS: [&[StatShard]; 3] = [
+    &[StatShard::AdaptiveForce, StatShard::AttackSpeed, StatShard::AbilityHaste],
+    &[StatShard::AdaptiveForce, StatShard::Armor, StatShard::MagicResist],
+    &[StatShard::ScalingHealth, StatShard::Armor, StatShard::MagicResist],
+];
+
+/// A rune page as it is written in a file: runes are referred to by name or id and the trees are
+/// taken from the runes themselves. Use `RuneRegistry::validate_page` before using a page. Only the
+/// keystone and the shards affect a fight; the minor runes are validated but have no effect yet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RunePage {
+    pub(crate) name: String,
+    pub(crate) keystone: String,
+    pub(crate) primary: [String; 3], // One rune from each minor row of the keystone's tree
+    pub(crate) secondary: [String; 2], // Two runes from different rows of another tree
+    pub(crate) shards: [StatShard; 3],
+}
+
+impl fmt::Display for RuneTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RuneTree::Precision => "Precision",
+            RuneTree::Domination => "Domination",
+            RuneTree::Sorcery => "Sorcery",
+            RuneTree::Resolve => "Resolve",
+            RuneTree::Inspiration => "Inspiration",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl StatShard {
+    /// Add the shard's stats to the bonus stats of a champion at `level`. Adaptive force becomes AP
+    /// when the champion has more AP than bonus AD, and AD otherwise, so it should be added after
+    /// item stats.
+    pub fn add_stats(&self, stats: &mut ChampStats, level: i32) {
+        match self {
+            StatShard::AdaptiveForce => {
+                if stats.ap > stats.bonus_ad {
+                    stats.ap += ADAPTIVE_FORCE;
+                } else {
+                    stats.bonus_ad += ADAPTIVE_FORCE * ADAPTIVE_FORCE_AD_RATIO;
+                }
+            }
+            StatShard::AttackSpeed => stats.bonus_as += SHARD_ATTACK_SPEED,
+            StatShard::AbilityHaste => stats.ability_haste += SHARD_ABILITY_HASTE,
+            StatShard::Armor => stats.bonus_armor += SHARD_ARMOR,
+            StatShard::MagicResist => stats.bonus_mr += SHARD_MR,
+            StatShard::ScalingHealth => stats.bonus_health += scaling_health(level),
+        }
+    }
+}
+
+/// Health from the scaling health shard, linear from level 1 to 18.
+fn scaling_health(level: i32) -> f32 {
+    let level = level.clamp(1, 18);
+
+    SHARD_HEALTH_MIN + (SHARD_HEALTH_MAX - SHARD_HEALTH_MIN) * (level - 1) as f32 / 17.0
+}
+
+impl RunePage {
+    /// The primary and secondary runes, which are not simulated.
+    pub fn minor_runes(&self) -> impl Iterator<Item = &str> {
+        self.primary.iter().chain(&self.secondary).map(String::as_str)
+    }
+
+    pub fn add_stats(&self, stats: &mut ChampStats, level: i32) {
+        for shard in &self.shards {
+            shard.add_stats(stats, level);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rune::{scaling_health, RunePage, StatShard};
+    use crate::utils::create_champion_by_name;
+
+    #[test]
+    fn test_scaling_health() {
+        assert_eq!(scaling_health(1), 15.0);
+        assert_eq!(scaling_health(18), 140.0);
+        assert_eq!(scaling_health(30), 140.0);
+    }
+
+    #[test]
+    fn test_adaptive_force() {
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+
+        StatShard::AdaptiveForce.add_stats(&mut champion.champ_stats, 1);
+        assert_eq!(champion.champ_stats.bonus_ad, 5.4);
+        assert_eq!(champion.champ_stats.ap, 0.0);
+
+        champion.champ_stats.ap = 50.0;
+        StatShard::AdaptiveForce.add_stats(&mut champion.champ_stats, 1);
+        assert_eq!(champion.champ_stats.bonus_ad, 5.4);
+        assert_eq!(champion.champ_stats.ap, 59.0);
+    }
+
+    #[test]
+    fn test_rune_page_stats() {
+        let page: RunePage = serde_json::from_str(r#"{
+            "name": "test",
+            "keystone": "Conqueror",
+            "primary": ["Triumph", "Legend: Alacrity", "Last Stand"],
+            "secondary": ["Bone Plating", "Unflinching"],
+            "shards": ["attack_speed", "armor", "scaling_health"]
+        }"#).unwrap();
+        let mut champion = create_champion_by_name("test-bruiser").unwrap();
+
+        page.add_stats(&mut champion.champ_stats, 18);
+
+        assert_eq!(champion.champ_stats.bonus_as, 0.1);
+        assert_eq!(champion.champ_stats.bonus_armor, 6.0);
+        assert_eq!(champion.champ_stats.bonus_health, 140.0);
+
+        // Only the shards add stats
+        assert_eq!(page.minor_runes().collect::<Vec<_>>(), vec!["Triumph", "Legend: Alacrity", "Last Stand", "Bone Plating", "Unflinching"]);
+        assert_eq!(champion.champ_stats.bonus_ad, 0.0);
+    }
+}
@@ -1,64 +1,377 @@
-use std::time::Duration;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::ability::{AbilitySlot, SkillOrder};
 use crate::build::Build;
 use crate::champion::Champion;
-use crate::constants::{TICKS_PER_SECOND};
+use crate::champion::stats::ResourceType;
+use crate::constants::{HEALTH_REGEN_INTERVAL, MAX_FIGHT_DURATION, TICKS_PER_SECOND};
+use crate::crit::CritModel;
 use crate::damage::Damage;
+use crate::data::DataError;
+use crate::effects::EffectTrigger;
+use crate::data::patch::{GameData, LIVE_PATCH};
+use crate::rotation::{Accuracy, Action, ExecutionProfile, Rotation, RotationState};
+use crate::rune::RunePage;
 
 pub struct Scenario {
     pub first_actor: u8, // 0 = you, 1 = enemy; maybe should be an enum
-    pub first_hit_reaction_delay: Duration, // In seconds
     pub champ1_build: Build,
-    // TODO: Champ1 ability rotation, hit chance
+    pub champ1_rotation: Rotation,
+    pub champ1_accuracy: Accuracy,
+    pub champ1_execution: ExecutionProfile,
     pub champ2_build: Build,
-    // TODO: Champ2 ability rotation, hit chance
+    pub champ2_rotation: Rotation,
+    pub champ2_accuracy: Accuracy,
+    pub champ2_execution: ExecutionProfile,
+    pub crit_model: CritModel, // Used by both sides
+    pub seed: Option<u64>, // For every random decision; picked at random and logged when not set
+    pub patch: Option<String>,
+    pub overlays: Vec<String>,
+    pub verbose: bool, // Print every action; turned off for Monte Carlo runs
+    pub(crate) log_lines: Option<RefCell<Vec<String>>>, // When set, verbose output is collected here instead of printed
+}
+
+/// A description of one side of a scenario that does not depend on any particular data set, so the
+/// same fight can be resolved against several patches.
+#[derive(Debug, Clone)]
+pub struct BuildSetup {
+    pub champion: String,
+    pub level: i32,
+    pub items: Vec<String>, // Item names or ids
+    pub rune_page: Option<RunePage>,
+    pub ability_ranks: BTreeMap<AbilitySlot, usize>, // Replaces the skill order when not empty
+    pub skill_order: Option<SkillOrder>, // Overrides the champion's own skill order
+}
+
+#[derive(Debug, Clone)]
+pub struct ScenarioSetup {
+    pub first_actor: u8,
+    pub champ1: BuildSetup,
+    pub champ2: BuildSetup,
+    pub champ1_rotation: Rotation,
+    pub champ2_rotation: Rotation,
+    pub champ1_accuracy: Accuracy,
+    pub champ2_accuracy: Accuracy,
+    pub champ1_execution: ExecutionProfile,
+    pub champ2_execution: ExecutionProfile,
+    pub crit_model: CritModel,
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CombatantResult {
+    pub(crate) name: String,
+    pub(crate) level: i32,
+    pub(crate) damage_dealt: Damage,
+    pub(crate) health_remaining: f32,
+    pub(crate) health_regenerated: f32,
+    pub(crate) healing_done: f32, // From vamp, abilities and the keystone; regeneration is kept apart
+    pub(crate) shield_absorbed: BTreeMap<String, f32>, // Damage absorbed by each shield source
+    pub(crate) keystone: Option<KeystoneResult>,
+    pub(crate) abilities: Vec<AbilityResult>,
+}
+
+/// What a keystone contributed over the fight. Its damage is also part of `damage_dealt`.
+#[derive(Debug, Clone)]
+pub struct KeystoneResult {
+    pub(crate) name: String,
+    pub(crate) damage_dealt: Damage,
+    pub(crate) healing_done: f32,
+}
+
+/// The damage one learned ability dealt over the fight. It is also part of `damage_dealt`.
+#[derive(Debug, Clone)]
+pub struct AbilityResult {
+    pub(crate) slot: AbilitySlot,
+    pub(crate) name: String,
+    pub(crate) rank: usize,
+    pub(crate) damage_dealt: Damage,
+    pub(crate) casts: i32,
+    pub(crate) hits: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScenarioResult {
+    pub(crate) patch: Option<String>,
+    pub(crate) overlays: Vec<String>,
+    pub(crate) seed: u64, // Replays the same fight when set as the scenario's seed
+    pub(crate) winner: Option<u8>, // Same convention as `Scenario::first_actor`; `None` for a draw
+    pub(crate) duration: f32, // In seconds
+    pub(crate) champ1: CombatantResult,
+    pub(crate) champ2: CombatantResult,
+}
+
+impl BuildSetup {
+    pub fn new(champion: &str, level: i32) -> BuildSetup {
+        BuildSetup {
+            champion: champion.to_string(),
+            level,
+            items: Vec::new(),
+            rune_page: None,
+            ability_ranks: BTreeMap::new(),
+            skill_order: None,
+        }
+    }
+
+    pub fn with_items(mut self, items: &[&str]) -> BuildSetup {
+        self.items = items.iter().map(|item| item.to_string()).collect();
+        self
+    }
+
+    pub fn with_rune_page(mut self, rune_page: RunePage) -> BuildSetup {
+        self.rune_page = Some(rune_page);
+        self
+    }
+
+    pub fn with_ability_rank(mut self, slot: AbilitySlot, rank: usize) -> BuildSetup {
+        self.ability_ranks.insert(slot, rank);
+        self
+    }
+
+    pub fn with_skill_order(mut self, skill_order: SkillOrder) -> BuildSetup {
+        self.skill_order = Some(skill_order);
+        self
+    }
+
+    pub fn resolve(&self, data: &GameData) -> Result<Build, DataError> {
+        let mut champion = data.create_champion(&self.champion)?;
+
+        if self.skill_order.is_some() {
+            champion.set_skill_order(self.skill_order.clone());
+        }
+
+        // Abilities without an explicit rank are not learned
+        if !self.ability_ranks.is_empty() {
+            champion.set_skill_order(None);
+        }
+
+        champion.set_level(self.level);
+
+        for (slot, rank) in &self.ability_ranks {
+            champion.set_ability_rank(*slot, *rank);
+        }
+
+        let items = self.items.iter()
+            .map(|item| data.find_item(item))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut build = Build::new(&champion, items);
+
+        if let Some(rune_page) = &self.rune_page {
+            data.runes.validate_page(rune_page)?;
+
+            // Pages may refer to the keystone by id
+            let mut rune_page = rune_page.clone();
+            rune_page.keystone = data.runes.find(&rune_page.keystone)?.name.clone();
+
+            build = build.with_rune_page(rune_page);
+        }
+
+        Ok(build)
+    }
+}
+
+impl ScenarioSetup {
+    /// A fight between two builds with the default rotations, accuracy, execution and crit model,
+    /// where the first champion acts first.
+    pub fn new(champ1: BuildSetup, champ2: BuildSetup) -> ScenarioSetup {
+        ScenarioSetup {
+            first_actor: 0,
+            champ1,
+            champ2,
+            champ1_rotation: Rotation::default(),
+            champ2_rotation: Rotation::default(),
+            champ1_accuracy: Accuracy::default(),
+            champ2_accuracy: Accuracy::default(),
+            champ1_execution: ExecutionProfile::default(),
+            champ2_execution: ExecutionProfile::default(),
+            crit_model: CritModel::default(),
+            seed: None,
+        }
+    }
+
+    pub fn resolve(&self, data: &GameData) -> Result<Scenario, DataError> {
+        let mut scenario = Scenario::new(
+            self.first_actor,
+            self.champ1.resolve(data)?,
+            self.champ2.resolve(data)?,
+        );
+        scenario.champ1_rotation = self.champ1_rotation.clone();
+        scenario.champ2_rotation = self.champ2_rotation.clone();
+        scenario.champ1_accuracy = self.champ1_accuracy;
+        scenario.champ2_accuracy = self.champ2_accuracy;
+        scenario.champ1_execution = self.champ1_execution;
+        scenario.champ2_execution = self.champ2_execution;
+        scenario.crit_model = self.crit_model;
+        scenario.seed = self.seed;
+        scenario.patch = data.patch.clone();
+        scenario.overlays = data.overlays.clone();
+
+        Ok(scenario)
+    }
+}
+
+impl CombatantResult {
+    fn new(champion: &Champion, damage_dealt: Damage, health_regenerated: f32) -> CombatantResult {
+        CombatantResult {
+            name: champion.name.clone(),
+            level: champion.level,
+            damage_dealt,
+            health_remaining: champion.champ_stats.health,
+            health_regenerated,
+            healing_done: champion.healing_done,
+            shield_absorbed: champion.shields.absorbed.clone(),
+            keystone: champion.keystone.as_ref().map(|keystone| KeystoneResult {
+                name: keystone.keystone.name().to_string(),
+                damage_dealt: keystone.damage_dealt,
+                healing_done: keystone.healing_done,
+            }),
+            abilities: champion.abilities.values()
+                .filter(|ability| ability.rank > 0)
+                .map(|ability| AbilityResult {
+                    slot: ability.ability.slot,
+                    name: ability.ability.name.clone(),
+                    rank: ability.rank,
+                    damage_dealt: ability.damage_dealt,
+                    casts: ability.casts,
+                    hits: ability.hits,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ScenarioResult {
+    /// The champion left standing, or `None` if the fight hit the time limit or both died on the same
+    /// tick.
+    pub fn winner(&self) -> Option<&CombatantResult> {
+        match self.winner {
+            Some(0) => Some(&self.champ1),
+            Some(_) => Some(&self.champ2),
+            None => None,
+        }
+    }
+
+    /// The winner's name, or "Draw".
+    pub fn winner_name(&self) -> &str {
+        self.winner().map_or("Draw", |winner| winner.name.as_str())
+    }
 }
 
 impl Scenario {
 
-    pub fn new(first_actor: u8, first_hit_reaction_delay: Duration, champ1_build: Build, champ2_build: Build) -> Scenario {
+    pub fn new(first_actor: u8, champ1_build: Build, champ2_build: Build) -> Scenario {
         Scenario {
             first_actor,
-            first_hit_reaction_delay,
             champ1_build,
             champ2_build,
+            champ1_rotation: Rotation::default(),
+            champ2_rotation: Rotation::default(),
+            champ1_accuracy: Accuracy::default(),
+            champ2_accuracy: Accuracy::default(),
+            champ1_execution: ExecutionProfile::default(),
+            champ2_execution: ExecutionProfile::default(),
+            crit_model: CritModel::default(),
+            seed: None,
+            patch: None,
+            overlays: Vec::new(),
+            verbose: true,
+            log_lines: None,
         }
     }
 
-    pub fn calculate_scenario(&mut self) {
+    pub fn calculate_scenario(&mut self) -> ScenarioResult {
         let mut tick = 0;
-        let first_hit_reaction_delay_in_ticks = (TICKS_PER_SECOND * self.first_hit_reaction_delay.as_secs_f32()).round() as i32;
+        let seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let mut champ1 = self.champ1_build.built_champion();
+        let mut champ2 = self.champ2_build.built_champion();
+        champ1.set_crit_model(self.crit_model);
+        champ2.set_crit_model(self.crit_model);
+
+        // The champion that does not act first only starts after its reaction delay
+        let (champ1_first_tick, champ2_first_tick) = if self.champ1_acts_first() { (0, self.champ2_execution.reaction_ticks()) } else { (self.champ1_execution.reaction_ticks(), 0) };
+        let mut champ1_rotation = RotationState::new(self.champ1_rotation.clone(), champ1_first_tick).with_execution(self.champ1_execution);
+        let mut champ2_rotation = RotationState::new(self.champ2_rotation.clone(), champ2_first_tick).with_execution(self.champ2_execution);
+
+        let mut champ1_damage_dealt = Damage::new(0.0, 0.0, 0.0);
+        let mut champ2_damage_dealt = Damage::new(0.0, 0.0, 0.0);
+        let mut champ1_health_regenerated = 0.0;
+        let mut champ2_health_regenerated = 0.0;
+
+        self.log(format_args!("Calculating scenario between:"));
+
+        let sides = [
+            (&champ1, &self.champ1_build, &self.champ1_rotation, self.champ1_accuracy, self.champ1_execution),
+            (&champ2, &self.champ2_build, &self.champ2_rotation, self.champ2_accuracy, self.champ2_execution),
+        ];
+
+        for (champion, build, rotation, accuracy, execution) in sides {
+            if build.items.is_empty() {
+                self.log(format_args!("{} at level {}", champion.name, champion.level));
+            } else {
+                self.log(format_args!("{} at level {} with {}", champion.name, champion.level, build.item_names().join(", ")));
+            }
+
+            if let Some(rune_page) = &build.rune_page {
+                self.log(format_args!("  Runes: {} ({})", rune_page.keystone, rune_page.name));
+                self.log(format_args!("  Minor runes without effect: {}", rune_page.minor_runes().collect::<Vec<_>>().join(", ")));
+            }
+
+            self.log(format_args!("  Rotation: {} ({} accuracy, {} execution)", rotation, accuracy, execution));
 
-        let mut champ1 = self.champ1_build.champion.clone();
-        let champ1_as_in_ticks = (TICKS_PER_SECOND / champ1.champ_stats.as_).round() as i32;
+            if uses_resource(champion) {
+                self.log(format_args!("  Resource: {} {} ({} per 5 seconds)", champion.champ_stats.resource, champion.champ_stats.resource_type, champion.champ_stats.rp5));
+            }
+
+            if champion.champ_stats.ability_haste > 0.0 || champion.champ_stats.ultimate_haste > 0.0 {
+                self.log(format_args!("  Ability haste: {} ({} ultimate haste)", champion.champ_stats.ability_haste, champion.champ_stats.ultimate_haste));
+            }
+        }
+        self.log(format_args!("Crits: {}", self.crit_model));
+        self.log(format_args!("Seed: {}", seed));
+        self.log(format_args!("Patch: {}", self.patch.as_deref().unwrap_or(LIVE_PATCH)));
+
+        for overlay in &self.overlays {
+            self.log(format_args!("Overlay: {}", overlay));
+        }
 
-        let mut champ2 = self.champ2_build.champion.clone();
-        let champ2_as_in_ticks = (TICKS_PER_SECOND / champ2.champ_stats.as_).round() as i32;
+        self.log(format_args!("{} ({}) will attack first", if self.first_actor == 0 { champ1.name.as_str() } else { champ2.name.as_str() }, if self.first_actor == 0 { champ1.level } else { champ2.level }));
+        self.log(format_args!("First hit reaction delay: {} seconds", if self.champ1_acts_first() { self.champ2_execution.reaction_delay } else { self.champ1_execution.reaction_delay }));
+        self.log(format_args!(""));
 
-        println!("Calculating scenario between:");
-        println!("{} at level {}", champ1.name, champ1.level);
-        println!("{} at level {}", champ2.name, champ2.level);
-        println!("{} ({}) will attack first", if self.first_actor == 0 { champ1.name.as_str() } else { champ2.name.as_str() }, if self.first_actor == 0 { champ1.level } else { champ2.level });
-        println!("First hit reaction delay: {} seconds", self.first_hit_reaction_delay.as_secs_f32());
-        println!();
 
+        let max_ticks = (MAX_FIGHT_DURATION * TICKS_PER_SECOND) as i32;
+        let regen_ticks = (HEALTH_REGEN_INTERVAL * TICKS_PER_SECOND).round() as i32;
 
-        while champ1.champ_stats.health > 0.0 && champ2.champ_stats.health > 0.0 {
-            let mut total_damage = Damage::new(0.0, 0.0, 0.0);
+        while champ1.champ_stats.health > 0.0 && champ2.champ_stats.health > 0.0 && tick < max_ticks {
+            // Both champions act on the same tick, so a killing blow does not stop the other's action
+            let champ1_action_damage = self.take_turn(tick, &mut champ1, &mut champ1_rotation, self.champ1_accuracy, &mut champ2, &mut rng);
+            let champ2_action_damage = self.take_turn(tick, &mut champ2, &mut champ2_rotation, self.champ2_accuracy, &mut champ1, &mut rng);
 
-            total_damage += self.calculate_aa_damage_and_side_effects(
-                tick,
-                first_hit_reaction_delay_in_ticks,
-                &mut champ1, champ1_as_in_ticks,
-                &mut champ2, champ2_as_in_ticks
-            );
+            if champ1.champ_stats.health > 0.0 && champ2.champ_stats.health > 0.0 {
+                self.apply_on_hit_effects(&mut champ2, &mut champ1, EffectTrigger::Aura);
+                self.apply_on_hit_effects(&mut champ1, &mut champ2, EffectTrigger::Aura);
+            }
+
+            let (champ1_dot_damage, champ2_dot_damage) = self.calculate_dot_damage(tick, &mut champ1, &mut champ2);
 
-            // TODO: Test this in scenario
-            total_damage += self.calculate_dot_damage(tick, &mut champ1, &mut champ2);
+            champ1_damage_dealt += champ1_action_damage + champ1_dot_damage;
+            champ2_damage_dealt += champ2_action_damage + champ2_dot_damage;
 
-            // TODO: Check stacking effect damage
+            let total_damage = champ1_action_damage + champ1_dot_damage + champ2_action_damage + champ2_dot_damage;
 
             if total_damage.total() > 0.0 {
-                println!("A total of {} damage was dealt this tick. \n", total_damage.total());
+                self.log(format_args!("A total of {} damage was dealt this tick. \n", total_damage.total()));
+            }
+
+            if (tick + 1) % regen_ticks == 0 {
+                champ1_health_regenerated += champ1.regenerate_health();
+                champ2_health_regenerated += champ2.regenerate_health();
             }
 
             champ1.decrement_own_effect_time_left();
@@ -69,112 +382,489 @@ impl Scenario {
             tick += 1;
         }
 
-        if champ1.champ_stats.health <= 0.0 {
-            println!("{} ({}) wins!", champ2.name, champ2.level);
-        } else {
-            println!("{} ({}) wins!", champ1.name, champ2.level);
+        let result = ScenarioResult {
+            patch: self.patch.clone(),
+            overlays: self.overlays.clone(),
+            seed,
+            winner: match (champ1.champ_stats.health > 0.0, champ2.champ_stats.health > 0.0) {
+                (true, false) => Some(0),
+                (false, true) => Some(1),
+                _ => None,
+            },
+            duration: tick as f32 / TICKS_PER_SECOND,
+            champ1: CombatantResult::new(&champ1, champ1_damage_dealt, champ1_health_regenerated),
+            champ2: CombatantResult::new(&champ2, champ2_damage_dealt, champ2_health_regenerated),
+        };
+
+        if tick >= max_ticks {
+            self.log(format_args!("The fight was stopped after {} seconds.", MAX_FIGHT_DURATION));
         }
 
-        println!("{} ({}): {}", champ1.name, champ1.level, champ1.champ_stats.health);
-        println!("{} ({}): {}", champ2.name, champ1.level, champ2.champ_stats.health);
-        println!("The fight would have lasted {} seconds.", tick as f32 / TICKS_PER_SECOND);
-    }
+        match result.winner() {
+            Some(winner) => self.log(format_args!("{} ({}) wins!", winner.name, winner.level)),
+            None => self.log(format_args!("The fight is a draw.")),
+        }
 
-    fn apply_duration_on_hit_effects(&mut self, receiver: &mut Champion, giver: &mut Champion) {
-        for (_, effect) in giver.friendly_duration_on_hit_effects.iter() {
-            receiver.apply_enemy_dot_on_hit_effect(effect.clone());
+        self.log(format_args!("{} ({}): {}/{} health", champ1.name, champ1.level, result.champ1.health_remaining, champ1.champ_stats.max_health));
+        self.log(format_args!("{} ({}): {}/{} health", champ2.name, champ2.level, result.champ2.health_remaining, champ2.champ_stats.max_health));
+        self.log(format_args!("The fight would have lasted {} seconds.", result.duration));
+
+        for combatant in [&result.champ1, &result.champ2] {
+            if combatant.health_regenerated > 0.0 {
+                self.log(format_args!("{} ({}) regenerated {} health", combatant.name, combatant.level, combatant.health_regenerated));
+            }
+
+            if combatant.healing_done > 0.0 {
+                self.log(format_args!("{} ({}) healed {} health", combatant.name, combatant.level, combatant.healing_done));
+            }
+
+            for (source, absorbed) in &combatant.shield_absorbed {
+                self.log(format_args!("{} ({})'s {} shield absorbed {} damage", combatant.name, combatant.level, source, absorbed));
+            }
+
+            if let Some(keystone) = &combatant.keystone {
+                self.log(format_args!("{} ({})'s {}: {} damage, {} healing", combatant.name, combatant.level, keystone.name, keystone.damage_dealt.total(), keystone.healing_done));
+            }
+
+            for ability in &combatant.abilities {
+                self.log(format_args!("{} ({})'s {} ({}): {} damage, {} casts, {} hit", combatant.name, combatant.level, ability.slot, ability.name, ability.damage_dealt.total(), ability.casts, ability.hits));
+            }
         }
+
+        result
     }
 
-    fn apply_stacking_on_hit_effects(&mut self, receiver: &mut Champion, giver: &mut Champion) {
-        for (_, effect) in giver.friendly_stacking_on_hit_effects.iter() {
-            receiver.apply_enemy_stacking_on_hit_effect(effect.clone());
+    /// Apply the `giver`'s damage over time and stacking effects with the `trigger` to the `receiver`.
+    fn apply_on_hit_effects(&mut self, receiver: &mut Champion, giver: &mut Champion, trigger: EffectTrigger) {
+        for effect in giver.friendly_duration_on_hit_effects.values().filter(|effect| effect.trigger == trigger) {
+            receiver.apply_enemy_dot_on_hit_effect(effect.snapshot(&giver.champ_stats));
+        }
+
+        for effect in giver.friendly_stacking_on_hit_effects.values().filter(|effect| effect.trigger == trigger) {
+            receiver.apply_enemy_stacking_on_hit_effect(effect.snapshot(&giver.champ_stats));
         }
     }
 
-    fn calculate_dot_damage(&mut self, tick: i32, champ1: &mut Champion, champ2: &mut Champion) -> Damage {
+    /// Returns the dot damage dealt by each champion this tick, as (champ1, champ2). The champion
+    /// that applied a dot heals from its vamp.
+    fn calculate_dot_damage(&mut self, tick: i32, champ1: &mut Champion, champ2: &mut Champion) -> (Damage, Damage) {
         let damage1 = champ1.calculate_and_apply_dot_effects(tick);
         let damage2 = champ2.calculate_and_apply_dot_effects(tick);
 
+        champ2.heal(champ2.champ_stats.vamp_healing(damage1, false, false));
+        champ1.heal(champ1.champ_stats.vamp_healing(damage2, false, false));
+
         if damage1.total() > 0.0 {
-            println!("{tick} | {} ({}) takes {} dot damage!", champ1.name, champ1.level, damage1.total());
+            self.log(format_args!("{tick} | {} ({}) takes {} dot damage!", champ1.name, champ1.level, damage1.total()));
         }
 
         if damage2.total() > 0.0 {
-            println!("{tick} | {} ({}) takes {} dot damage!", champ2.name, champ2.level, damage2.total());
+            self.log(format_args!("{tick} | {} ({}) takes {} dot damage!", champ2.name, champ2.level, damage2.total()));
         }
 
-        damage1 + damage2
+        (damage2, damage1)
     }
 
-    fn calculate_aa_damage_and_side_effects(&mut self, tick: i32,
-                                            first_hit_reaction_delay_in_ticks: i32, mut champ1: &mut Champion,
-                                            champ1_as_in_ticks: i32, mut champ2: &mut Champion,
-                                            champ2_as_in_ticks: i32) -> Damage {
-        if tick == 0 {
-            return if self.champ1_acts_first() {
-                let damage = champ2.take_auto_attack_damage(&mut champ1);
-                self.apply_stacking_on_hit_effects(champ2, champ1);
-                self.apply_duration_on_hit_effects(champ2, champ1);
+    /// Let `actor` do the next action of its rotation, if it can act this tick, and land its attack
+    /// if the windup is over. Returns the damage dealt.
+    fn take_turn(&mut self, tick: i32, actor: &mut Champion, rotation: &mut RotationState, accuracy: Accuracy, target: &mut Champion, rng: &mut StdRng) -> Damage {
+        // Crowd control that stops the champion from acting interrupts an attack that is winding up
+        if !actor.can_act() {
+            rotation.cancel_attack(tick);
+        }
 
-                println!("{tick} | {} ({}) attacks {} ({}) for {} damage!", champ1.name, champ1.level, champ2.name, champ2.level, damage.total());
+        let mut damage = self.land_attack(tick, actor, rotation, target, rng);
 
-                damage
-            } else {
-                let damage = champ1.take_auto_attack_damage(&mut champ2);
-                self.apply_stacking_on_hit_effects(champ1, champ2);
-                self.apply_duration_on_hit_effects(champ1, champ2);
+        // Attack speed can change mid-fight, e.g. with Lethal Tempo stacks
+        let attack_time = attack_time_in_ticks(actor.champ_stats.as_);
 
-                println!("{tick} | {} ({}) attacks {} ({}) for {} damage!", champ2.name, champ2.level, champ1.name, champ2.level, damage.total());
+        let action = rotation.next_action(tick, actor, target, attack_time.is_some());
 
-                damage
+        for skipped in rotation.take_skipped() {
+            let reason = match skipped {
+                Action::AutoAttack => "it cannot attack",
+                Action::Cast(_) => "it is not learned",
+            };
+
+            self.log(format_args!("{tick} | {} ({}) skips {} in its combo, {}", actor.name, actor.level, skipped, reason));
+        }
+
+        match action {
+            Some(Action::AutoAttack) => {
+                let attack_time = attack_time.unwrap_or(1.0);
+                rotation.attacked(tick, attack_time, actor.champ_stats.attack_windup * attack_time);
+
+                let mistimed_attack_chance = rotation.execution.mistimed_attack_chance;
+
+                if mistimed_attack_chance > 0.0 && rng.gen::<f32>() < mistimed_attack_chance {
+                    rotation.mistime_attack();
+                }
+
+                // Attacks with a windup shorter than a tick land right away
+                damage += self.land_attack(tick, actor, rotation, target, rng);
             }
-        } else if tick == first_hit_reaction_delay_in_ticks {
-            return if self.champ1_acts_first() {
-                let damage = champ2.take_auto_attack_damage(&mut champ1);
-                self.apply_stacking_on_hit_effects(champ2, champ1);
-                self.apply_duration_on_hit_effects(champ2, champ1);
+            Some(Action::Cast(slot)) => {
+                let hit_chance = actor.abilities.get(&slot).map_or(1.0, |ability| accuracy.hit_chance(&ability.ability, target));
+                let hits = hit_chance >= 1.0 || rng.gen::<f32>() < hit_chance;
 
-                println!("{tick} | {} ({}) attacks {} ({}) for {} damage!", champ1.name, champ1.level, champ2.name, champ2.level, damage.total());
+                damage += self.cast(tick, actor, rotation, target, slot, hits);
+            }
+            None => {}
+        }
 
-                damage
-            } else {
-                let damage = champ1.take_auto_attack_damage(&mut champ2);
-                self.apply_stacking_on_hit_effects(champ1, champ2);
-                self.apply_duration_on_hit_effects(champ1, champ2);
+        damage
+    }
+
+    /// Deal the damage of `actor`'s attack if its windup ends this tick.
+    fn land_attack(&mut self, tick: i32, actor: &mut Champion, rotation: &mut RotationState, target: &mut Champion, rng: &mut StdRng) -> Damage {
+        if !rotation.attack_lands(tick) {
+            return Damage::new(0.0, 0.0, 0.0);
+        }
 
-                println!("{tick} | {} ({}) attacks {} ({}) for {} damage!", champ2.name, champ2.level, champ1.name, champ1.level, damage.total());
+        let damage = target.take_auto_attack_damage(actor, rng);
+        self.apply_on_hit_effects(target, actor, EffectTrigger::Attack);
+
+        self.log(format_args!("{tick} | {} ({}) attacks {} ({}) for {} damage!", actor.name, actor.level, target.name, target.level, damage.total()));
+        self.log_keystone_hit(actor);
+        self.log(format_args!("Remaining health: {}", target.champ_stats.health));
+
+        damage
+    }
+
+    /// Cast the ability in `slot` at `target`, which takes its damage unless the cast misses.
+    fn cast(&mut self, tick: i32, actor: &mut Champion, rotation: &mut RotationState, target: &mut Champion, slot: AbilitySlot, hits: bool) -> Damage {
+        // Damage lands when the cast starts; the cast time only delays the next action
+        let (cast_time, resets_attack) = actor.abilities.get(&slot)
+            .map_or((0.0, false), |ability| (ability.ability.cast_time, ability.ability.resets_attack));
+        rotation.cast(tick, cast_time);
+
+        if resets_attack {
+            rotation.reset_attack();
+        }
 
+        if !hits {
+            actor.cast_ability(slot, &target.champ_stats);
+
+            self.log(format_args!("{tick} | {} ({}) casts {} at {} ({}) and misses!", actor.name, actor.level, slot, target.name, target.level));
+
+            return Damage::new(0.0, 0.0, 0.0);
+        }
+
+        let damage = match target.take_ability_damage(actor, slot) {
+            Some(damage) => {
+                self.apply_on_hit_effects(target, actor, EffectTrigger::Ability);
                 damage
             }
-        } else if tick > first_hit_reaction_delay_in_ticks {
-            let mut damage = Damage::new(0.0, 0.0, 0.0);
+            None => Damage::new(0.0, 0.0, 0.0),
+        };
 
-            if tick % champ1_as_in_ticks == 0 {
-                damage += champ2.take_auto_attack_damage(&mut champ1);
-                self.apply_stacking_on_hit_effects(champ2, champ1);
-                self.apply_duration_on_hit_effects(champ2, champ1);
+        self.log(format_args!("{tick} | {} ({}) casts {} on {} ({}) for {} damage!", actor.name, actor.level, slot, target.name, target.level, damage.total()));
+        self.log_keystone_hit(actor);
 
-                println!("{tick} | {} ({}) attacks {} ({}) for {} damage!", champ1.name, champ1.level, champ2.name, champ2.level, damage.total());
-            }
+        if uses_resource(actor) {
+            self.log(format_args!("{} ({}) has {} {} left", actor.name, actor.level, actor.champ_stats.resource, actor.champ_stats.resource_type));
+        }
+        self.log(format_args!("Remaining health: {}", target.champ_stats.health));
 
-            if tick % champ2_as_in_ticks == 0 {
-                damage += champ1.take_auto_attack_damage(&mut champ2);
-                self.apply_stacking_on_hit_effects(champ1, champ2);
-                self.apply_duration_on_hit_effects(champ1, champ2);
+        damage
+    }
 
-                println!("{tick} | {} ({}) attacks {} ({}) for {} damage!", champ2.name, champ2.level, champ1.name, champ1.level, damage.total());
+    /// Log what the latest hit triggered on `actor`'s keystone, if anything.
+    fn log_keystone_hit(&self, actor: &Champion) {
+        if let Some(keystone) = &actor.keystone {
+            if let Some(hit) = keystone.last_hit {
+                self.log(format_args!("{} ({})'s {} deals {} damage and heals {}", actor.name, actor.level, keystone.keystone.name(), hit.damage.total(), hit.healing));
             }
-
-            return damage;
         }
+    }
 
-        // TODO: Consider auto cancels
+    fn log(&self, message: fmt::Arguments) {
+        if !self.verbose {
+            return;
+        }
 
-        Damage::new(0.0, 0.0, 0.0)
+        match &self.log_lines {
+            Some(lines) => lines.borrow_mut().push(message.to_string()),
+            None => println!("{}", message),
+        }
     }
 
     fn champ1_acts_first(&self) -> bool {
         self.first_actor == 0
     }
 }
+
+/// Whether the champion's abilities spend a resource bar, rather than health or nothing.
+fn uses_resource(champion: &Champion) -> bool {
+    matches!(champion.champ_stats.resource_type, ResourceType::Mana | ResourceType::Energy | ResourceType::Fury)
+}
+
+/// Ticks between auto attacks, or `None` for champions that cannot attack (e.g. training dummies).
+/// Not rounded, so the attack timer can keep the fraction.
+fn attack_time_in_ticks(attack_speed: f32) -> Option<f32> {
+    if attack_speed <= 0.0 || !attack_speed.is_finite() {
+        return None;
+    }
+
+    Some((TICKS_PER_SECOND / attack_speed).max(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use crate::ability::AbilitySlot;
+    use crate::crit::CritModel;
+    use crate::data::{data_dir, read_json_file};
+    use crate::data::patch::GameData;
+    use crate::rotation::{Accuracy, ExecutionProfile, Rotation};
+    use crate::rune::RunePage;
+    use crate::scenario::{attack_time_in_ticks, BuildSetup, ScenarioSetup};
+
+    fn setup(champ1: BuildSetup) -> ScenarioSetup {
+        ScenarioSetup {
+            seed: Some(0),
+            ..ScenarioSetup::new(champ1, BuildSetup::new("aatrox", 6))
+        }
+    }
+
+    #[test]
+    fn test_scenario_uses_build_items() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        let naked = setup(BuildSetup::new("test-bruiser", 6)).resolve(&data).unwrap().calculate_scenario();
+        let built = setup(BuildSetup::new("test-bruiser", 6).with_items(&["B. F. Sword", "Dagger"]))
+            .resolve(&data).unwrap().calculate_scenario();
+
+        assert!(built.champ1.damage_dealt.total() / built.duration > naked.champ1.damage_dealt.total() / naked.duration);
+        assert!(built.duration <= naked.duration);
+    }
+
+    #[test]
+    fn test_ability_haste_adds_casts() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let fight = |items: &[&str]| {
+            let mut setup = setup(BuildSetup::new("test-bruiser", 6).with_items(items).with_ability_rank(AbilitySlot::Q, 1));
+            setup.champ2 = BuildSetup::new("dummy", 1);
+            setup.champ1_rotation = Rotation::parse_priority("Q").unwrap();
+
+            setup.resolve(&data).unwrap().calculate_scenario()
+        };
+
+        let without_haste = fight(&[]);
+        let with_haste = fight(&["Ionian Boots of Lucidity"]);
+
+        // The fight lasts until the time limit, so only the cooldown decides the number of casts
+        assert!(with_haste.champ1.abilities[0].casts > without_haste.champ1.abilities[0].casts);
+        assert_eq!(without_haste.winner, None);
+        assert_eq!(without_haste.winner_name(), "Draw");
+    }
+
+    #[test]
+    fn test_health_regenerates_during_fight() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        let without_bead = setup(BuildSetup::new("test-bruiser", 6)).resolve(&data).unwrap().calculate_scenario();
+        let with_bead = setup(BuildSetup::new("test-bruiser", 6).with_items(&["Rejuvenation Bead"]))
+            .resolve(&data).unwrap().calculate_scenario();
+
+        assert!(without_bead.champ1.health_regenerated > 0.0);
+        assert!(without_bead.champ2.health_regenerated > 0.0);
+        assert!(with_bead.champ1.health_regenerated > without_bead.champ1.health_regenerated);
+    }
+
+    #[test]
+    fn test_scenario_casts_learned_abilities() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        let autos_only = setup(BuildSetup::new("test-bruiser", 6)).resolve(&data).unwrap().calculate_scenario();
+        let result = setup(BuildSetup::new("test-bruiser", 6)
+            .with_ability_rank(AbilitySlot::Q, 3)
+            .with_ability_rank(AbilitySlot::W, 2)
+            .with_ability_rank(AbilitySlot::R, 1))
+            .resolve(&data).unwrap().calculate_scenario();
+
+        let slots: Vec<AbilitySlot> = result.champ1.abilities.iter().map(|ability| ability.slot).collect();
+
+        assert_eq!(slots, vec![AbilitySlot::Q, AbilitySlot::W, AbilitySlot::R]);
+        assert!(result.champ1.abilities.iter().all(|ability| ability.damage_dealt.total() > 0.0));
+        assert!(result.champ1.abilities[1].damage_dealt.magical_component > 0.0);
+        assert!(result.champ2.abilities.is_empty());
+        assert!(result.duration < autos_only.duration);
+    }
+
+    #[test]
+    fn test_scenario_follows_combo() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let champ1 = BuildSetup::new("test-bruiser", 6)
+            .with_ability_rank(AbilitySlot::Q, 3)
+            .with_ability_rank(AbilitySlot::W, 2);
+
+        let default = setup(champ1.clone()).resolve(&data).unwrap().calculate_scenario();
+
+        let mut combo = setup(champ1);
+        combo.champ1_rotation = Rotation::parse_combo("Q AA AA E").unwrap();
+        let combo = combo.resolve(&data).unwrap().calculate_scenario();
+
+        let mut target = data.create_champion("aatrox").unwrap();
+        target.set_level(6);
+
+        // Q is cast once, W never and E is not learned; the rest of the fight is auto attacks
+        assert_eq!(combo.champ1.abilities[0].damage_dealt.total(), (100.0 / (100.0 + target.champ_stats.armor)) * 100.0);
+        assert_eq!(combo.champ1.abilities[1].damage_dealt.total(), 0.0);
+        assert!(combo.duration > default.duration);
+    }
+
+    #[test]
+    fn test_scenario_follows_priority_list() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        let mut setup = setup(BuildSetup::new("test-bruiser", 6).with_ability_rank(AbilitySlot::R, 1));
+        setup.champ1_rotation = Rotation::parse_priority("R if target < 30%, AA").unwrap();
+        let result = setup.resolve(&data).unwrap().calculate_scenario();
+
+        let r = &result.champ1.abilities[0];
+
+        assert_eq!(r.slot, AbilitySlot::R);
+        assert!(r.damage_dealt.true_component > 0.0);
+        assert_eq!(result.winner, Some(0));
+    }
+
+    #[test]
+    fn test_ability_damage_is_mitigated_like_autos() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let mut caster = data.create_champion("test-bruiser").unwrap();
+        let mut target = data.create_champion("aatrox").unwrap();
+        caster.set_ability_rank(AbilitySlot::Q, 1);
+
+        let health = target.champ_stats.health;
+        let damage = target.take_ability_damage(&mut caster, AbilitySlot::Q).unwrap();
+
+        // 40 physical damage against 38 armor
+        assert_eq!(damage.physical_component, 40.0 * 100.0 / 138.0);
+        assert_eq!(target.champ_stats.health, (health - damage.physical_component).round());
+        assert!(target.take_ability_damage(&mut caster, AbilitySlot::Q).is_none());
+    }
+
+    #[test]
+    fn test_attack_time_in_ticks() {
+        assert_eq!(attack_time_in_ticks(1.0), Some(30.0));
+        assert_eq!(attack_time_in_ticks(0.0), None);
+        assert_eq!(attack_time_in_ticks(100.0), Some(1.0));
+        assert_eq!(attack_time_in_ticks(0.8), Some(37.5));
+    }
+
+    #[test]
+    fn test_scenario_against_training_dummy() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let mut setup = setup(BuildSetup::new("aatrox", 18).with_items(&["B. F. Sword", "B. F. Sword", "Blade of the Ruined King"]));
+        setup.champ2 = BuildSetup::new("dummy", 1);
+
+        let result = setup.resolve(&data).unwrap().calculate_scenario();
+
+        assert_eq!(result.winner, Some(0));
+        assert_eq!(result.champ2.damage_dealt.total(), 0.0);
+    }
+
+    #[test]
+    fn test_item_effects_deal_damage() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let fight = |items: &[&str], dummy_items: &[&str]| {
+            let mut setup = setup(BuildSetup::new("test-bruiser", 6).with_items(items).with_ability_rank(AbilitySlot::Q, 1));
+            setup.champ2 = BuildSetup::new("dummy", 1).with_items(dummy_items);
+            setup.champ1_rotation = Rotation::parse_priority("Q").unwrap();
+
+            setup.resolve(&data).unwrap().calculate_scenario()
+        };
+
+        let naked = fight(&[], &[]);
+        let liandrys = fight(&["Liandry's Torment"], &[]);
+        let sunfire = fight(&[], &["Sunfire Aegis"]);
+
+        // Q is physical, so the magic damage comes from the burn on ability hits
+        assert_eq!(naked.champ1.damage_dealt.magical_component, 0.0);
+        assert!(liandrys.champ1.damage_dealt.magical_component > 0.0);
+
+        // The training dummy never attacks, but its aura burns the other champion
+        assert_eq!(naked.champ2.damage_dealt.total(), 0.0);
+        assert!(sunfire.champ2.damage_dealt.magical_component > 0.0);
+    }
+
+    #[test]
+    fn test_scenario_reports_keystone() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let page: RunePage = read_json_file(&data_dir().unwrap().join("rune-pages").join("aatrox-conqueror.json")).unwrap();
+
+        let naked = setup(BuildSetup::new("test-bruiser", 6)).resolve(&data).unwrap().calculate_scenario();
+        let result = setup(BuildSetup::new("test-bruiser", 6).with_rune_page(page)).resolve(&data).unwrap().calculate_scenario();
+
+        let keystone = result.champ1.keystone.as_ref().unwrap();
+
+        assert_eq!(keystone.name, "Conqueror");
+        assert!(keystone.healing_done > 0.0);
+        assert!(result.champ2.keystone.is_none());
+        assert!(naked.champ1.keystone.is_none());
+    }
+
+    #[test]
+    fn test_scenario_reports_healing() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        let naked = setup(BuildSetup::new("test-bruiser", 6)).resolve(&data).unwrap().calculate_scenario();
+        let result = setup(BuildSetup::new("test-bruiser", 6).with_items(&["Vampiric Scepter"])).resolve(&data).unwrap().calculate_scenario();
+
+        assert!(result.champ1.healing_done > 0.0);
+        assert_eq!(result.champ2.healing_done, 0.0);
+        assert_eq!(naked.champ1.healing_done, 0.0);
+    }
+
+    #[test]
+    fn test_same_seed_replays_the_fight() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let mut setup = setup(BuildSetup::new("aatrox", 11).with_ability_rank(AbilitySlot::Q, 5).with_ability_rank(AbilitySlot::W, 3));
+        setup.champ1_accuracy = Accuracy::Skill(0.5);
+        setup.champ1_execution = ExecutionProfile::realistic();
+        setup.crit_model = CritModel::PseudoRandom;
+
+        let fight = |seed: u64| {
+            let mut setup = setup.clone();
+            setup.seed = Some(seed);
+
+            let mut scenario = setup.resolve(&data).unwrap();
+            scenario.log_lines = Some(RefCell::new(Vec::new()));
+            let result = scenario.calculate_scenario();
+
+            (result, scenario.log_lines.unwrap().into_inner())
+        };
+
+        // Every logged action is the same, not just the totals
+        let (first, first_log) = fight(7);
+        let (second, second_log) = fight(7);
+
+        assert!(first_log.len() > 20);
+        assert_eq!(first_log, second_log);
+        assert_eq!(first.seed, 7);
+        assert_eq!(second.duration, first.duration);
+
+        // Misses and mistimed attacks change with the seed
+        let outcomes: Vec<(f32, f32)> = (0..10).map(fight).map(|(result, _)| (result.duration, result.champ1.damage_dealt.total())).collect();
+        assert!(outcomes.iter().any(|outcome| *outcome != outcomes[0]));
+    }
+
+    #[test]
+    fn test_resolve_keystone_by_id() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let mut page: RunePage = read_json_file(&data_dir().unwrap().join("rune-pages").join("aatrox-conqueror.json")).unwrap();
+        page.keystone = String::from("8010");
+
+        let scenario = setup(BuildSetup::new("test-bruiser", 6).with_rune_page(page)).resolve(&data).unwrap();
+
+        assert_eq!(scenario.champ1_build.rune_page.unwrap().keystone, "Conqueror");
+    }
+
+    #[test]
+    fn test_resolve_unknown_item() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        assert!(setup(BuildSetup::new("test-bruiser", 6).with_items(&["Long Swrod"])).resolve(&data).is_err());
+    }
+}
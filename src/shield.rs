@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::champion::stats::ChampStats;
+use crate::constants::TICK_SECOND;
+use crate::effects::DamageFormula;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShieldType {
+    All,
+    Magic,
+    Physical,
+}
+
+/// A shield that loses its amount evenly from `delay` seconds after it is applied until it expires,
+/// like Sterak's Gage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShieldDecay {
+    pub(crate) delay: f32, // In seconds
+}
+
+/// A shield on a champion. Damage of the shield's type is taken from it before health.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shield {
+    pub(crate) source: String,
+    pub(crate) kind: ShieldType,
+    pub(crate) amount: f32, // Left to absorb; goes down with damage and decay
+    pub(crate) initial_amount: f32,
+    pub(crate) duration: Duration,
+    pub(crate) time_left: Duration,
+    pub(crate) decay: Option<ShieldDecay>,
+}
+
+/// The shields on a champion, and how much damage each source has absorbed over the fight.
+#[derive(Debug, Clone, Default)]
+pub struct ShieldStack {
+    pub(crate) shields: Vec<Shield>,
+    pub(crate) absorbed: BTreeMap<String, f32>,
+}
+
+/// A shield as it is defined in champion data, e.g. on an ability. `amount` is evaluated with the
+/// caster as the source and the champion it is cast at as the target.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShieldEffect {
+    pub(crate) kind: ShieldType,
+    pub(crate) amount: DamageFormula,
+    pub(crate) duration: f32, // In seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) decay: Option<ShieldDecay>,
+}
+
+impl Shield {
+    pub fn new(source: &str, kind: ShieldType, amount: f32, duration: Duration) -> Shield {
+        Shield {
+            source: source.to_string(),
+            kind,
+            amount,
+            initial_amount: amount,
+            duration,
+            time_left: duration,
+            decay: None,
+        }
+    }
+
+    pub fn with_decay(mut self, decay: ShieldDecay) -> Shield {
+        self.decay = Some(decay);
+        self
+    }
+
+    /// Advance the shield by one tick, decaying it if it has started to.
+    pub fn tick(&mut self) {
+        let tick = Duration::from_secs_f32(TICK_SECOND);
+        self.time_left = self.time_left.saturating_sub(tick);
+
+        if let Some(decay) = self.decay {
+            let decay_time = self.duration.as_secs_f32() - decay.delay;
+            let elapsed = (self.duration - self.time_left).as_secs_f32();
+
+            if decay_time > 0.0 && elapsed > decay.delay {
+                self.amount = (self.amount - self.initial_amount / decay_time * TICK_SECOND).max(0.0);
+            }
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.time_left.is_zero() || self.amount <= 0.0
+    }
+}
+
+impl ShieldStack {
+    pub fn add(&mut self, shield: Shield) {
+        self.shields.push(shield);
+    }
+
+    /// Take `damage` of a type that `kind` shields block from the shields. Shields of that kind go
+    /// first, then shields against all damage, each starting with the one that expires soonest.
+    /// Returns the damage that got through.
+    pub fn absorb(&mut self, mut damage: f32, kind: ShieldType) -> f32 {
+        let mut order: Vec<usize> = (0..self.shields.len()).collect();
+        order.sort_by_key(|&index| (self.shields[index].kind == ShieldType::All, self.shields[index].time_left));
+
+        for index in order {
+            let shield = &mut self.shields[index];
+
+            if damage <= 0.0 {
+                break;
+            }
+
+            if shield.kind != kind && shield.kind != ShieldType::All {
+                continue;
+            }
+
+            let absorbed = shield.amount.min(damage);
+            shield.amount -= absorbed;
+            damage -= absorbed;
+
+            *self.absorbed.entry(shield.source.clone()).or_insert(0.0) += absorbed;
+        }
+
+        self.shields.retain(|shield| !shield.is_expired());
+
+        damage
+    }
+
+    /// Advance every shield by one tick and drop the ones that ran out.
+    pub fn tick(&mut self) {
+        for shield in self.shields.iter_mut() {
+            shield.tick();
+        }
+
+        self.shields.retain(|shield| !shield.is_expired());
+    }
+}
+
+impl ShieldEffect {
+    pub fn shield(&self, source: &str, caster: &ChampStats, target: &ChampStats) -> Shield {
+        let shield = Shield::new(source, self.kind, self.amount.amount(caster, target), Duration::from_secs_f32(self.duration));
+
+        match self.decay {
+            Some(decay) => shield.with_decay(decay),
+            None => shield,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::constants::TICKS_PER_SECOND;
+    use crate::shield::{Shield, ShieldDecay, ShieldStack, ShieldType};
+
+    fn stack() -> ShieldStack {
+        let mut stack = ShieldStack::default();
+        stack.add(Shield::new("Barrier", ShieldType::All, 100.0, Duration::from_secs(2)));
+        stack.add(Shield::new("Hexdrinker", ShieldType::Magic, 50.0, Duration::from_secs(5)));
+        stack.add(Shield::new("Short", ShieldType::All, 30.0, Duration::from_secs(1)));
+
+        stack
+    }
+
+    /// The amount left on shields of exactly `kind`.
+    fn amount(stack: &ShieldStack, kind: ShieldType) -> f32 {
+        stack.shields.iter()
+            .filter(|shield| shield.kind == kind)
+            .map(|shield| shield.amount)
+            .sum()
+    }
+
+    #[test]
+    fn test_absorb_order() {
+        let mut stack = stack();
+
+        // Magic shields first, then the shield that expires soonest
+        assert_eq!(stack.absorb(70.0, ShieldType::Magic), 0.0);
+        assert_eq!(amount(&stack, ShieldType::Magic), 0.0);
+        assert_eq!(amount(&stack, ShieldType::All), 110.0);
+        assert_eq!(stack.absorbed["Hexdrinker"], 50.0);
+        assert_eq!(stack.absorbed["Short"], 20.0);
+
+        assert_eq!(stack.absorb(150.0, ShieldType::Physical), 40.0);
+        assert!(stack.shields.is_empty());
+        assert_eq!(stack.absorbed["Short"], 30.0);
+        assert_eq!(stack.absorbed["Barrier"], 100.0);
+    }
+
+    #[test]
+    fn test_physical_damage_skips_magic_shields() {
+        let mut stack = stack();
+
+        assert_eq!(stack.absorb(200.0, ShieldType::Physical), 70.0);
+        assert_eq!(amount(&stack, ShieldType::Magic), 50.0);
+        assert!(!stack.absorbed.contains_key("Hexdrinker"));
+    }
+
+    #[test]
+    fn test_shields_expire() {
+        let mut stack = stack();
+
+        for _ in 0..TICKS_PER_SECOND as i32 {
+            stack.tick();
+        }
+
+        assert_eq!(stack.shields.len(), 2);
+
+        for _ in 0..TICKS_PER_SECOND as i32 {
+            stack.tick();
+        }
+
+        assert_eq!(stack.shields.len(), 1);
+        assert_eq!(amount(&stack, ShieldType::Magic), 50.0);
+    }
+
+    #[test]
+    fn test_shield_decay() {
+        let mut shield = Shield::new("Lifeline", ShieldType::All, 300.0, Duration::from_secs(4)).with_decay(ShieldDecay { delay: 1.0 });
+
+        for _ in 0..(TICKS_PER_SECOND * 0.5) as i32 {
+            shield.tick();
+        }
+
+        assert_eq!(shield.amount, 300.0);
+
+        for _ in 0..(TICKS_PER_SECOND * 2.0) as i32 {
+            shield.tick();
+        }
+
+        // Half of the decay time has passed
+        assert!((shield.amount - 150.0).abs() < 1.0);
+        assert!(!shield.is_expired());
+    }
+}
@@ -1,3 +1,420 @@
 // TODO: Consider how to run simulations.
 // Simulations are a collection of scenarios that are run in parallel.
 // The outcome of all the simulations is then aggregated and analyzed.
+use std::fmt;
+use rand::Rng;
+use crate::ability::AbilitySlot;
+use crate::constants::MAX_LEVEL;
+use crate::data::DataError;
+use crate::data::patch::GameData;
+use crate::rotation::ExecutionProfile;
+use crate::scenario::{Scenario, ScenarioResult, ScenarioSetup};
+
+const PERCENTILES: [(&str, f32); 5] = [("min", 0.0), ("p10", 0.1), ("median", 0.5), ("p90", 0.9), ("max", 1.0)];
+
+/// The same scenario run under two patches.
+#[derive(Debug, Clone)]
+pub struct PatchComparison {
+    pub(crate) before: ScenarioResult,
+    pub(crate) after: ScenarioResult,
+}
+
+pub fn compare_patches(setup: &ScenarioSetup, before: &GameData, after: &GameData) -> Result<PatchComparison, DataError> {
+    let setup = with_fixed_seed(setup);
+    let before_result = setup.resolve(before)?.calculate_scenario();
+    let after_result = setup.resolve(after)?.calculate_scenario();
+
+    Ok(PatchComparison {
+        before: before_result,
+        after: after_result,
+    })
+}
+
+/// The outcomes of running the same scenario many times. Runs only differ when something is random,
+/// like crits and skillshots that can miss.
+#[derive(Debug, Clone)]
+pub struct OutcomeDistribution {
+    pub(crate) seed: u64, // Run n is seeded with `seed + n`
+    pub(crate) results: Vec<ScenarioResult>,
+}
+
+/// Run a scenario `runs` times without printing each fight. Each run gets its own seed, counting up
+/// from the scenario's seed, so the whole distribution can be reproduced.
+pub fn run_monte_carlo(scenario: &mut Scenario, runs: usize) -> OutcomeDistribution {
+    let verbose = scenario.verbose;
+    let scenario_seed = scenario.seed;
+    let seed = scenario_seed.unwrap_or_else(|| rand::thread_rng().gen());
+    scenario.verbose = false;
+
+    let results = (0..runs)
+        .map(|run| {
+            scenario.seed = Some(seed.wrapping_add(run as u64));
+            scenario.calculate_scenario()
+        })
+        .collect();
+
+    scenario.verbose = verbose;
+    scenario.seed = scenario_seed;
+
+    OutcomeDistribution { seed, results }
+}
+
+/// A copy of `setup` with a seed, so the scenarios run from it make the same random decisions.
+fn with_fixed_seed(setup: &ScenarioSetup) -> ScenarioSetup {
+    let mut setup = setup.clone();
+    setup.seed = Some(setup.seed.unwrap_or_else(|| rand::thread_rng().gen()));
+
+    setup
+}
+
+/// The same scenario with the first champion played perfectly and as an average player would.
+#[derive(Debug, Clone)]
+pub struct ExecutionComparison {
+    pub(crate) perfect: OutcomeDistribution,
+    pub(crate) realistic: OutcomeDistribution,
+}
+
+/// Run the scenario `runs` times with the first champion playing perfectly and `runs` times with a
+/// realistic execution profile, keeping the same build and seeds. The target plays as it does in
+/// `setup`.
+pub fn compare_execution(setup: &ScenarioSetup, data: &GameData, runs: usize) -> Result<ExecutionComparison, DataError> {
+    let setup = with_fixed_seed(setup);
+    let distribution = |execution: ExecutionProfile| -> Result<OutcomeDistribution, DataError> {
+        let mut setup = setup.clone();
+        setup.champ1_execution = execution;
+
+        Ok(run_monte_carlo(&mut setup.resolve(data)?, runs))
+    };
+
+    Ok(ExecutionComparison {
+        perfect: distribution(ExecutionProfile::default())?,
+        realistic: distribution(ExecutionProfile::realistic())?,
+    })
+}
+
+/// The first champion's results against the same target at every level, for power spike curves.
+#[derive(Debug, Clone)]
+pub struct LevelSweep {
+    pub(crate) results: Vec<ScenarioResult>, // One per level, from level 1
+}
+
+/// Run the scenario with the first champion at every level. The target keeps the level it has in
+/// `setup`, and ability ranks follow the first champion's skill order unless they are given
+/// explicitly.
+pub fn level_sweep(setup: &ScenarioSetup, data: &GameData) -> Result<LevelSweep, DataError> {
+    let setup = with_fixed_seed(setup);
+    let results = (1..=MAX_LEVEL)
+        .map(|level| {
+            let mut setup = setup.clone();
+            setup.champ1.level = level;
+
+            let mut scenario = setup.resolve(data)?;
+            scenario.verbose = false;
+
+            Ok(scenario.calculate_scenario())
+        })
+        .collect::<Result<_, DataError>>()?;
+
+    Ok(LevelSweep { results })
+}
+
+impl OutcomeDistribution {
+    /// The fraction of runs won by `side`, using the convention of `Scenario::first_actor`.
+    pub fn win_rate(&self, side: u8) -> f32 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+
+        self.results.iter().filter(|result| result.winner == Some(side)).count() as f32 / self.results.len() as f32
+    }
+
+    /// The fraction of runs that ended in a draw, most often at the time limit.
+    pub fn draw_rate(&self) -> f32 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+
+        self.results.iter().filter(|result| result.winner.is_none()).count() as f32 / self.results.len() as f32
+    }
+
+    pub fn durations(&self) -> Vec<f32> {
+        sorted(self.results.iter().map(|result| result.duration).collect())
+    }
+
+    pub fn champ1_damage(&self) -> Vec<f32> {
+        sorted(self.results.iter().map(|result| result.champ1.damage_dealt.total()).collect())
+    }
+
+    pub fn champ2_damage(&self) -> Vec<f32> {
+        sorted(self.results.iter().map(|result| result.champ2.damage_dealt.total()).collect())
+    }
+
+    pub fn champ1_dps(&self) -> Vec<f32> {
+        sorted(self.results.iter().map(|result| result.champ1.damage_dealt.total() / result.duration).collect())
+    }
+}
+
+fn sorted(mut values: Vec<f32>) -> Vec<f32> {
+    values.sort_by(f32::total_cmp);
+    values
+}
+
+/// The value at `fraction` (0 to 1) of sorted `values`, using the nearest rank.
+fn percentile(values: &[f32], fraction: f32) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    values[((values.len() - 1) as f32 * fraction).round() as usize]
+}
+
+impl PatchComparison {
+    /// Change in fight length, in seconds. Negative values mean the fight got shorter.
+    pub fn time_to_kill_change(&self) -> f32 {
+        self.after.duration - self.before.duration
+    }
+
+    pub fn champ1_damage_change(&self) -> f32 {
+        self.after.champ1.damage_dealt.total() - self.before.champ1.damage_dealt.total()
+    }
+
+    pub fn champ2_damage_change(&self) -> f32 {
+        self.after.champ2.damage_dealt.total() - self.before.champ2.damage_dealt.total()
+    }
+}
+
+impl ExecutionComparison {
+    /// The first champion's median DPS with realistic play as a fraction of its median DPS with
+    /// perfect play.
+    pub fn dps_ratio(&self) -> f32 {
+        let perfect = percentile(&self.perfect.champ1_dps(), 0.5);
+
+        if perfect > 0.0 { percentile(&self.realistic.champ1_dps(), 0.5) / perfect } else { 0.0 }
+    }
+}
+
+impl fmt::Display for PatchComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let before_patch = self.before.patch.as_deref().unwrap_or("live");
+        let after_patch = self.after.patch.as_deref().unwrap_or("live");
+
+        writeln!(f, "{:<28} {:>12} {:>12} {:>12}", "", before_patch, after_patch, "change")?;
+        writeln!(f, "{:<28} {:>12.2} {:>12.2} {:>+12.2}", "Time to kill (s)", self.before.duration, self.after.duration, self.time_to_kill_change())?;
+
+        let rows = [
+            (&self.before.champ1, &self.after.champ1, self.champ1_damage_change()),
+            (&self.before.champ2, &self.after.champ2, self.champ2_damage_change()),
+        ];
+
+        for (before, after, change) in rows {
+            let label = format!("{} ({}) damage", before.name, before.level);
+            writeln!(f, "{:<28} {:>12.0} {:>12.0} {:>+12.0}", label, before.damage_dealt.total(), after.damage_dealt.total(), change)?;
+        }
+
+        write!(f, "{:<28} {:>12} {:>12}", "Winner", self.before.winner_name(), self.after.winner_name())?;
+
+        for (label, result) in [("before", &self.before), ("after", &self.after)] {
+            for overlay in &result.overlays {
+                write!(f, "\nOverlay ({}): {}", label, overlay)?;
+            }
+        }
+
+        write!(f, "\nSeed: {}", self.before.seed)
+    }
+}
+
+impl fmt::Display for OutcomeDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(first) = self.results.first() else {
+            return write!(f, "No runs");
+        };
+
+        write!(f, "{:<28}", format!("{} runs", self.results.len()))?;
+
+        for (label, _) in PERCENTILES {
+            write!(f, " {:>8}", label)?;
+        }
+
+        let rows = [
+            (String::from("Time to kill (s)"), self.durations()),
+            (format!("{} ({}) damage", first.champ1.name, first.champ1.level), self.champ1_damage()),
+            (format!("{} ({}) damage", first.champ2.name, first.champ2.level), self.champ2_damage()),
+        ];
+
+        for (label, values) in rows {
+            write!(f, "\n{:<28}", label)?;
+
+            for (_, fraction) in PERCENTILES {
+                write!(f, " {:>8.1}", percentile(&values, fraction))?;
+            }
+        }
+
+        for (side, combatant) in [(0, &first.champ1), (1, &first.champ2)] {
+            write!(f, "\n{} ({}) wins {:.1}% of runs", combatant.name, combatant.level, self.win_rate(side) * 100.0)?;
+        }
+
+        write!(f, "\n{:.1}% of runs are a draw", self.draw_rate() * 100.0)?;
+
+        write!(f, "\nSeed: {}", self.seed)
+    }
+}
+
+impl fmt::Display for ExecutionComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(first) = self.perfect.results.first() else {
+            return write!(f, "No runs");
+        };
+
+        writeln!(f, "{:<28} {:>12} {:>12} {:>12}", format!("{} runs (medians)", self.perfect.results.len()), "perfect", "realistic", "change")?;
+
+        let rows = [
+            (String::from("Time to kill (s)"), self.perfect.durations(), self.realistic.durations()),
+            (format!("{} ({}) DPS", first.champ1.name, first.champ1.level), self.perfect.champ1_dps(), self.realistic.champ1_dps()),
+            (format!("{} ({}) damage", first.champ1.name, first.champ1.level), self.perfect.champ1_damage(), self.realistic.champ1_damage()),
+        ];
+
+        for (label, perfect, realistic) in rows {
+            let (perfect, realistic) = (percentile(&perfect, 0.5), percentile(&realistic, 0.5));
+            writeln!(f, "{:<28} {:>12.1} {:>12.1} {:>+12.1}", label, perfect, realistic, realistic - perfect)?;
+        }
+
+        writeln!(f, "{:<28} {:>11.1}% {:>11.1}%", format!("{} ({}) wins", first.champ1.name, first.champ1.level), self.perfect.win_rate(0) * 100.0, self.realistic.win_rate(0) * 100.0)?;
+        writeln!(f, "Realistic play keeps {:.1}% of the perfect DPS", self.dps_ratio() * 100.0)?;
+        write!(f, "Seed: {}", self.perfect.seed)
+    }
+}
+
+impl fmt::Display for LevelSweep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(first) = self.results.first() else {
+            return write!(f, "No levels");
+        };
+
+        writeln!(f, "{} against {} ({})", first.champ1.name, first.champ2.name, first.champ2.level)?;
+        write!(f, "Level Time to kill (s)     Damage      DPS Ranks          Winner")?;
+
+        for result in &self.results {
+            let damage = result.champ1.damage_dealt.total();
+            let ranks: Vec<String> = result.champ1.abilities.iter()
+                .filter(|ability| ability.slot != AbilitySlot::Passive)
+                .map(|ability| format!("{}{}", ability.slot, ability.rank))
+                .collect();
+
+            write!(f, "\n{:>5} {:>16.2} {:>10.0} {:>8.1} {:<14} {}", result.champ1.level, result.duration, damage, damage / result.duration, ranks.join(" "), result.winner_name())?;
+        }
+
+        write!(f, "\nSeed: {}", first.seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::data_dir;
+    use crate::ability::{AbilitySlot, SkillOrder};
+    use crate::data::patch::GameData;
+    use crate::rotation::Accuracy;
+    use crate::scenario::{BuildSetup, ScenarioSetup};
+    use crate::simulation::{compare_execution, compare_patches, level_sweep, percentile, run_monte_carlo};
+
+    #[test]
+    fn test_compare_patches() {
+        let setup = ScenarioSetup::new(BuildSetup::new("test-bruiser", 11), BuildSetup::new("aatrox", 11));
+
+        let before = GameData::load(&data_dir().unwrap(), Some("14.3")).unwrap();
+        let after = GameData::load(&data_dir().unwrap(), None).unwrap();
+
+        let comparison = compare_patches(&setup, &before, &after).unwrap();
+
+        assert_eq!(comparison.before.patch.as_deref(), Some("14.3"));
+        assert_eq!(comparison.after.patch, None);
+
+        // Aatrox has 35 more base health on live, so the test bruiser has to deal more damage
+        assert!(comparison.champ1_damage_change() > 0.0);
+        assert!(comparison.time_to_kill_change() >= 0.0);
+        assert_eq!(comparison.before.champ1.name, "Test Bruiser");
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+
+        assert_eq!(percentile(&values, 0.0), 1.0);
+        assert_eq!(percentile(&values, 0.5), 3.0);
+        assert_eq!(percentile(&values, 1.0), 5.0);
+        assert_eq!(percentile(&[], 0.5), 0.0);
+    }
+
+    #[test]
+    fn test_monte_carlo_with_skillshots() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let setup = ScenarioSetup {
+            champ1_accuracy: Accuracy::Skill(0.5),
+            ..ScenarioSetup::new(BuildSetup::new("aatrox", 11).with_ability_rank(AbilitySlot::Q, 5).with_ability_rank(AbilitySlot::W, 3), BuildSetup::new("test-bruiser", 11))
+        };
+
+        let mut scenario = setup.resolve(&data).unwrap();
+        let distribution = run_monte_carlo(&mut scenario, 50);
+
+        let casts: i32 = distribution.results.iter().flat_map(|result| &result.champ1.abilities).map(|ability| ability.casts).sum();
+        let hits: i32 = distribution.results.iter().flat_map(|result| &result.champ1.abilities).map(|ability| ability.hits).sum();
+
+        assert_eq!(distribution.results.len(), 50);
+        assert!(hits > 0 && hits < casts, "{} of {} casts hit", hits, casts);
+        assert_eq!(distribution.win_rate(0) + distribution.win_rate(1) + distribution.draw_rate(), 1.0);
+        assert!(scenario.verbose);
+        assert!(distribution.to_string().starts_with("50 runs"));
+    }
+
+    #[test]
+    fn test_monte_carlo_is_reproducible() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let setup = ScenarioSetup {
+            champ1_accuracy: Accuracy::Skill(0.5),
+            seed: Some(42),
+            ..ScenarioSetup::new(BuildSetup::new("aatrox", 11).with_ability_rank(AbilitySlot::Q, 5), BuildSetup::new("test-bruiser", 11))
+        };
+
+        let mut scenario = setup.resolve(&data).unwrap();
+        let first = run_monte_carlo(&mut scenario, 10);
+        let second = run_monte_carlo(&mut scenario, 10);
+
+        assert_eq!(first.seed, 42);
+        assert_eq!(first.results.iter().map(|result| result.seed).collect::<Vec<_>>(), (42..52).collect::<Vec<_>>());
+        assert_eq!(first.to_string(), second.to_string());
+        assert_eq!(scenario.seed, Some(42));
+    }
+
+    #[test]
+    fn test_level_sweep() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let setup = ScenarioSetup::new(BuildSetup::new("aatrox", 1).with_skill_order(SkillOrder::parse("Q>E>W").unwrap()), BuildSetup::new("test-bruiser", 9));
+
+        let sweep = level_sweep(&setup, &data).unwrap();
+        let levels: Vec<i32> = sweep.results.iter().map(|result| result.champ1.level).collect();
+
+        assert_eq!(levels, (1..=18).collect::<Vec<_>>());
+        assert!(sweep.results.iter().all(|result| result.champ2.level == 9));
+
+        // Ranks follow the skill order, and the ultimate is learned at 6
+        let ranks = |level: usize| -> Vec<(AbilitySlot, usize)> {
+            sweep.results[level - 1].champ1.abilities.iter().map(|ability| (ability.slot, ability.rank)).collect()
+        };
+
+        assert_eq!(ranks(1), vec![(AbilitySlot::Q, 1)]);
+        assert_eq!(ranks(6), vec![(AbilitySlot::Q, 3), (AbilitySlot::W, 1), (AbilitySlot::E, 1), (AbilitySlot::R, 1)]);
+        assert!(sweep.results[17].champ1.damage_dealt.total() > sweep.results[0].champ1.damage_dealt.total());
+    }
+
+    #[test]
+    fn test_compare_execution() {
+        let data = GameData::load(&data_dir().unwrap(), None).unwrap();
+        let setup = ScenarioSetup::new(BuildSetup::new("aatrox", 11).with_ability_rank(AbilitySlot::Q, 5).with_ability_rank(AbilitySlot::W, 3), BuildSetup::new("dummy", 1));
+
+        let comparison = compare_execution(&setup, &data, 10).unwrap();
+
+        assert_eq!(comparison.perfect.results.len(), 10);
+        assert!(comparison.dps_ratio() > 0.0 && comparison.dps_ratio() < 1.0, "{}", comparison.dps_ratio());
+        assert!(percentile(&comparison.realistic.durations(), 0.5) > percentile(&comparison.perfect.durations(), 0.5));
+        assert!(comparison.to_string().starts_with("10 runs"));
+    }
+}
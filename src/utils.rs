@@ -1,194 +1,103 @@
-use std::collections::HashMap;
 use crate::champion::Champion;
-use crate::champion::stats::ChampStats;
-
-pub fn create_champion_by_name(name: &str) -> Champion {
-    let lower_name = name.to_lowercase();
-
-    // TODO: Load these base values from a file or API
-    match lower_name.as_str() {
-        "aatrox" => Champion {
-            name: String::from("Aatrox"),
-            level: 1,
-            friendly_limited_use_on_hit_effects: HashMap::new(),
-            friendly_duration_on_hit_effects: HashMap::new(),
-            friendly_stacking_on_hit_effects: HashMap::new(),
-            enemy_dot_on_hit_effects: HashMap::new(),
-            enemy_stacking_on_hit_effects: HashMap::new(),
-            champ_stats: ChampStats {
-                base_health: 685.0,
-                base_health_growth: 114.0,
-                health: 685.0,
-                base_hp5: 3.0,
-                base_hp5_growth: 1.0,
-                hp5: 3,
-                base_resource: 0.0,
-                base_resource_growth: 0.0,
-                resource: 0,
-                base_rp5: 0.0,
-                base_rp5_growth: 0.0,
-                rp5: 0,
-                base_ad: 60.0,
-                base_ad_growth: 5.0,
-                ad: 60,
-                base_as: 0.651,
-                base_as_growth_percent: 0.025,
-                attack_windup: 0.23384,
-                as_: 0.651,
-                as_ratio: 0.651,
-                base_armor: 38.0,
-                base_armor_growth: 4.45,
-                armor: 38.0,
-                bonus_armor: 0.0,
-                base_mr: 32.0,
-                base_mr_growth: 2.05,
-                mr: 32.0,
-                bonus_mr: 0.0,
-                base_range: 175,
-                range: 175,
-                base_ms: 345,
-                ms: 345,
-                base_crit: 0.0,
-                crit: 0.50,
-                bonus_crit_percent: 0.0,
-                shield_amount: 0.0,
-                magic_shield_amount: 0.0,
-                physical_shield_amount: 0.0,
-                mr_pen: 0.0,
-                flat_mr_reduction: 0.0,
-                percent_mr_reduction: 0.0,
-                percent_mr_pen: 0.0,
-                lethality: 0.0,
-                percent_bonus_armor_pen: 0.0,
-                armor_reduction: 0.0,
-                percent_armor_reduction: 0.0,
-                life_steal: 0,
-                spell_vamp: 0,
-                tenacity: 0,
-            },
-        },
-        "test-bruiser" => Champion {
-            name: String::from("Test Bruiser"),
-            level: 1,
-            friendly_limited_use_on_hit_effects: HashMap::new(),
-            friendly_duration_on_hit_effects: HashMap::new(),
-            friendly_stacking_on_hit_effects: HashMap::new(),
-            enemy_dot_on_hit_effects: HashMap::new(),
-            enemy_stacking_on_hit_effects: HashMap::new(),
-            champ_stats: ChampStats {
-                base_health: 685.0,
-                base_health_growth: 114.0,
-                health: 685.0,
-                base_hp5: 3.0,
-                base_hp5_growth: 1.0,
-                hp5: 3,
-                base_resource: 0.0,
-                base_resource_growth: 0.0,
-                resource: 0,
-                base_rp5: 0.0,
-                base_rp5_growth: 0.0,
-                rp5: 0,
-                base_ad: 60.0,
-                base_ad_growth: 5.0,
-                ad: 60,
-                base_as: 0.651,
-                base_as_growth_percent: 0.025,
-                attack_windup: 0.23384,
-                as_: 0.651,
-                as_ratio: 0.651,
-                base_armor: 38.0,
-                base_armor_growth: 4.45,
-                armor: 38.0,
-                bonus_armor: 0.0,
-                base_mr: 32.0,
-                base_mr_growth: 2.05,
-                mr: 32.0,
-                bonus_mr: 0.0,
-                base_range: 175,
-                range: 175,
-                base_ms: 345,
-                ms: 345,
-                base_crit: 0.0,
-                crit: 0.00,
-                bonus_crit_percent: 0.0,
-                shield_amount: 0.0,
-                magic_shield_amount: 0.0,
-                physical_shield_amount: 0.0,
-                mr_pen: 0.0,
-                flat_mr_reduction: 0.0,
-                percent_mr_reduction: 0.0,
-                percent_mr_pen: 0.0,
-                lethality: 0.0,
-                percent_bonus_armor_pen: 0.0,
-                armor_reduction: 0.0,
-                percent_armor_reduction: 0.0,
-                life_steal: 0,
-                spell_vamp: 0,
-                tenacity: 0,
-            },
-        },
-        "dummy" => Champion {
-            name: String::from("Dummy"),
-            level: 1,
-            friendly_limited_use_on_hit_effects: HashMap::new(),
-            friendly_duration_on_hit_effects: HashMap::new(),
-            friendly_stacking_on_hit_effects: HashMap::new(),
-            enemy_dot_on_hit_effects: HashMap::new(),
-            enemy_stacking_on_hit_effects: HashMap::new(),
-            champ_stats: ChampStats {
-                base_health: 10000.0,
-                base_health_growth: 0.0,
-                health: 10000.0,
-                base_hp5: 0.0,
-                base_hp5_growth: 0.0,
-                hp5: 0,
-                base_resource: 0.0,
-                base_resource_growth: 0.0,
-                resource: 0,
-                base_rp5: 0.0,
-                base_rp5_growth: 0.0,
-                rp5: 0,
-                base_ad: 0.0,
-                base_ad_growth: 0.0,
-                ad: 0,
-                base_as: 0.0,
-                base_as_growth_percent: 0.0,
-                attack_windup: 0.0,
-                as_: 0.0,
-                as_ratio: 0.0,
-                base_armor: 0.0,
-                base_armor_growth: 0.0,
-                armor: 0.0,
-                bonus_armor: 0.0,
-                base_mr: 0.0,
-                base_mr_growth: 0.0,
-                mr: 0.0,
-                bonus_mr: 0.0,
-                base_range: 0,
-                range: 0,
-                base_ms: 0,
-                ms: 0,
-                base_crit: 0.0,
-                crit: 0.0,
-                bonus_crit_percent: 0.0,
-                shield_amount: 0.0,
-                magic_shield_amount: 0.0,
-                physical_shield_amount: 0.0,
-                mr_pen: 0.0,
-                flat_mr_reduction: 0.0,
-                percent_mr_reduction: 0.0,
-                percent_mr_pen: 0.0,
-                lethality: 0.0,
-                percent_bonus_armor_pen: 0.0,
-                armor_reduction: 0.0,
-                percent_armor_reduction: 0.0,
-                life_steal: 0,
-                spell_vamp: 0,
-                tenacity: 0,
-            },
-        },
-        _ => {
-            panic!("Champion not found: {}", name);
+use crate::data;
+use crate::data::DataError;
+
+pub fn create_champion_by_name(name: &str) -> Result<Champion, DataError> {
+    data::champion_registry()?.create_champion(name)
+}
+
+/// Lowercase a name and strip everything that is not a letter or digit, so that "K'Sante",
+/// "KSante" and "k sante" compare equal.
+pub fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Levenshtein distance between two strings.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Up to `limit` names whose key is closest to `query` by edit distance, closest first. Each
+/// candidate is a list of normalized keys (e.g. id and display name) and the name to report.
+pub fn closest_matches<'a>(query: &str, candidates: impl Iterator<Item = (Vec<String>, &'a str)>, limit: usize) -> Vec<String> {
+    let query = normalize_name(query);
+    let max_distance = (query.len() / 3).max(2);
+
+    let mut matches: Vec<(usize, &str)> = candidates
+        .filter_map(|(keys, name)| {
+            let distance = keys.iter().map(|key| edit_distance(&query, key)).min()?;
+            Some((distance, name))
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+
+    matches.sort();
+    matches.into_iter()
+        .take(limit)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::DataError;
+    use crate::utils::{closest_matches, create_champion_by_name, edit_distance, normalize_name};
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("K'Sante"), "ksante");
+        assert_eq!(normalize_name("Kai'Sa"), "kaisa");
+        assert_eq!(normalize_name("Dr. Mundo"), "drmundo");
+        assert_eq!(normalize_name("test-bruiser"), "testbruiser");
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("aatrox", "aatrox"), 0);
+        assert_eq!(edit_distance("atrox", "aatrox"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", ""), 3);
+    }
+
+    #[test]
+    fn test_closest_matches() {
+        let candidates = vec![
+            (vec![String::from("longsword")], "Long Sword"),
+            (vec![String::from("clotharmor")], "Cloth Armor"),
+            (vec![String::from("1036"), String::from("lonsword")], "Other Sword"),
+        ];
+
+        assert_eq!(closest_matches("longswrd", candidates.clone().into_iter(), 3), vec!["Long Sword", "Other Sword"]);
+        assert_eq!(closest_matches("longswrd", candidates.clone().into_iter(), 1), vec!["Long Sword"]);
+        assert!(closest_matches("boots", candidates.into_iter(), 3).is_empty());
+    }
+
+    #[test]
+    fn test_create_champion_by_name_unknown() {
+        match create_champion_by_name("Aatroks") {
+            Err(DataError::UnknownChampion { name, suggestions }) => {
+                assert_eq!(name, "Aatroks");
+                assert_eq!(suggestions, vec![String::from("Aatrox")]);
+            }
+            _ => panic!("Expected an unknown champion error"),
         }
     }
-}
\ No newline at end of file
+}